@@ -0,0 +1,93 @@
+//! Compares a single-threaded scan with the default parallel scan.
+//!
+//! ```sh
+//! cargo bench -p file-extractor-core --bench scan
+//! ```
+//!
+//! Scans a synthetic tree generated in the temp directory, or the directory
+//! in `SCAN_BENCH_DIR` (e.g. a network drive) when set.
+
+use file_extractor_core::{scan_tree, FileNode, ScanOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Subdirectories per directory and levels of the synthetic tree
+const FANOUT: usize = 6;
+const LEVELS: usize = 4;
+const FILES_PER_DIR: usize = 10;
+
+const ITERATIONS: usize = 10;
+
+fn main() {
+    let (root, generated) = match std::env::var_os("SCAN_BENCH_DIR") {
+        Some(dir) => (PathBuf::from(dir), false),
+        None => (generate_tree(), true),
+    };
+    let root_str = root.to_string_lossy().to_string();
+
+    let sequential = ScanOptions { max_depth: LEVELS + 1, threads: Some(1), ..ScanOptions::default() };
+    let parallel = ScanOptions { max_depth: LEVELS + 1, ..ScanOptions::default() };
+
+    let expected = scan_tree(&root_str, &sequential).expect("scan failed");
+    println!("{}: {} entries", root.display(), count(&expected));
+
+    let sequential_time = bench(&root_str, &sequential, &expected);
+    let parallel_time = bench(&root_str, &parallel, &expected);
+    println!("sequential: {:?} per scan", sequential_time);
+    println!("parallel:   {:?} per scan", parallel_time);
+    println!("speedup:    {:.2}x", sequential_time.as_secs_f64() / parallel_time.as_secs_f64());
+
+    if generated {
+        let _ = fs::remove_dir_all(&root);
+    }
+}
+
+/// Median time of a scan, checking that it matches `expected`
+fn bench(root: &str, options: &ScanOptions, expected: &[FileNode]) -> Duration {
+    let mut times: Vec<Duration> = (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            let tree = scan_tree(root, options).expect("scan failed");
+            let elapsed = start.elapsed();
+            assert_eq!(paths(&tree), paths(expected), "scan order changed");
+            elapsed
+        })
+        .collect();
+    times.sort();
+    times[ITERATIONS / 2]
+}
+
+fn generate_tree() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("file-extractor-scan-bench-{}", std::process::id()));
+    fill_dir(&root, LEVELS);
+    root
+}
+
+fn fill_dir(dir: &Path, levels: usize) {
+    fs::create_dir_all(dir).expect("failed to create bench directory");
+    for i in 0..FILES_PER_DIR {
+        fs::write(dir.join(format!("file_{}.rs", i)), "fn main() {}\n").expect("failed to write bench file");
+    }
+    if levels > 0 {
+        for i in 0..FANOUT {
+            fill_dir(&dir.join(format!("dir_{}", i)), levels - 1);
+        }
+    }
+}
+
+fn count(nodes: &[FileNode]) -> usize {
+    nodes.iter().map(|n| 1 + n.children.as_deref().map_or(0, count)).sum()
+}
+
+fn paths(nodes: &[FileNode]) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut stack: Vec<&FileNode> = nodes.iter().rev().collect();
+    while let Some(node) = stack.pop() {
+        out.push(node.path.as_str());
+        if let Some(children) = &node.children {
+            stack.extend(children.iter().rev());
+        }
+    }
+    out
+}
@@ -0,0 +1,234 @@
+//! Headless command line front end sharing the scan/generate logic of the app.
+//!
+//! Exit codes:
+//! - 0: every selected file was included
+//! - 1: total failure (bad arguments, nothing selected, output not writable,
+//!   or no file could be read)
+//! - 2: invalid command line (reported by the argument parser)
+//! - 3: partial failure (some files could not be included)
+//!
+//! With `--serve` the CLI instead runs the localhost HTTP/JSON API until
+//! killed. The bearer token comes from `--token`, the `FILE_EXTRACTOR_TOKEN`
+//! environment variable, or is generated and printed on startup.
+//!
+//! With `--mcp` it serves the Model Context Protocol over stdin/stdout.
+//!
+//! A `.file-extractor.toml` at the root (or a parent) supplies defaults for
+//! ignore rules, patterns, source name and output; flags take precedence.
+
+use clap::Parser;
+use file_extractor_core::{
+    discover_project_config, generate, generate_token, run_stdio_server, scan_tree, select_files,
+    ApiServer, FileLimits, FileNode, FileReport, GenerateOptions, InclusionMode, OutputFormat,
+    PathFilter, ProjectConfig, ScanOptions, SymlinkPolicy, DEFAULT_SERVER_PORT,
+};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const EXIT_TOTAL_FAILURE: u8 = 1;
+const EXIT_PARTIAL_FAILURE: u8 = 3;
+
+#[derive(Debug, Parser)]
+#[command(name = "file-extractor-cli", version, about = "Bundle source files into a single LLM context file")]
+struct Args {
+    /// Root directory to scan
+    #[arg(required_unless_present_any = ["serve", "mcp"])]
+    root: Option<PathBuf>,
+
+    /// Glob of files to include, relative to the root (repeatable; default: all)
+    #[arg(short, long = "include", value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Glob of files to exclude, relative to the root (repeatable)
+    #[arg(short, long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Source name written in the header (default: root folder name)
+    #[arg(short = 'n', long)]
+    source_name: Option<String>,
+
+    /// Output format (default: text)
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Output file; writes to stdout when omitted or `-`
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// Prefix every content line with its line number
+    #[arg(long)]
+    line_numbers: bool,
+
+    /// Strip comments and blank-line runs from supported languages
+    #[arg(long)]
+    minify: bool,
+
+    /// Emit only signatures and types for supported languages
+    #[arg(long)]
+    outline: bool,
+
+    /// Directory levels to scan below the root (default: 6)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_depth: Option<u64>,
+
+    /// Which symbolic links to list (default: follow-within-root)
+    #[arg(long, value_enum)]
+    symlinks: Option<SymlinkPolicy>,
+
+    /// Also scan dotfiles and dot-directories outside the default allowlist
+    #[arg(long)]
+    hidden: bool,
+
+    /// Append a unified diff of the selected files against this ref
+    #[arg(long, value_name = "REF")]
+    git_diff: Option<String>,
+
+    /// Append the last N commit messages touching the selected files
+    #[arg(long, value_name = "N")]
+    git_log: Option<usize>,
+
+    /// Run the localhost HTTP/JSON API instead of generating once
+    #[arg(long, conflicts_with = "mcp")]
+    serve: bool,
+
+    /// Run a Model Context Protocol server over stdio instead of generating once
+    #[arg(long)]
+    mcp: bool,
+
+    /// Port of the API server
+    #[arg(long, default_value_t = DEFAULT_SERVER_PORT, requires = "serve")]
+    port: u16,
+
+    /// Bearer token required by the API server
+    #[arg(long, requires = "serve")]
+    token: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if args.serve || args.mcp {
+        let served = if args.mcp { run_stdio_server() } else { serve(&args) };
+        return match served {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ExitCode::from(EXIT_TOTAL_FAILURE)
+            }
+        };
+    }
+    match run(&args) {
+        Ok(files) => {
+            let failed: Vec<&FileReport> = files.iter().filter(|f| f.error.is_some()).collect();
+            for file in &failed {
+                eprintln!("warning: {}: {}", file.path, file.error.as_deref().unwrap_or(""));
+            }
+            if failed.is_empty() {
+                ExitCode::SUCCESS
+            } else if failed.len() == files.len() {
+                eprintln!("error: none of the {} selected files could be read", files.len());
+                ExitCode::from(EXIT_TOTAL_FAILURE)
+            } else {
+                eprintln!("{} of {} files could not be included", failed.len(), files.len());
+                ExitCode::from(EXIT_PARTIAL_FAILURE)
+            }
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(EXIT_TOTAL_FAILURE)
+        }
+    }
+}
+
+fn serve(args: &Args) -> Result<(), String> {
+    let token = match args.token.clone().or_else(|| std::env::var("FILE_EXTRACTOR_TOKEN").ok()) {
+        Some(token) => token,
+        None => {
+            let token = generate_token()?;
+            eprintln!("token: {}", token);
+            token
+        }
+    };
+    let server = ApiServer::bind(args.port, &token)?;
+    eprintln!("listening on http://{}", server.local_addr()?);
+    server.run()
+}
+
+fn run(args: &Args) -> Result<Vec<FileReport>, String> {
+    let root = args.root.as_deref().ok_or("No root directory given")?;
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Cannot open {}: {}", root.display(), e))?;
+
+    let project = discover_project_config(&root)?
+        .map(|(_, config)| config)
+        .unwrap_or_default();
+
+    // Command line patterns replace the project's default selection
+    let filter = match &project.selection {
+        Some(selection) if args.include.is_empty() && args.exclude.is_empty() => selection.filter()?,
+        _ => PathFilter::new(&args.include, &args.exclude)?,
+    };
+    let mut scan_options = project.scan_options(ScanOptions::default());
+    if let Some(max_depth) = args.max_depth {
+        scan_options.max_depth = max_depth as usize;
+    }
+    if let Some(symlinks) = args.symlinks {
+        scan_options.symlinks = symlinks;
+    }
+    scan_options.include_hidden |= args.hidden;
+    let tree = scan_tree(&root.to_string_lossy(), &scan_options)?;
+    let truncated = count_truncated(&tree);
+    if truncated > 0 {
+        eprintln!(
+            "warning: {} directories below depth {} were not scanned; raise --max-depth to include them",
+            truncated, scan_options.max_depth
+        );
+    }
+    let selected = select_files(&tree, &root, &filter);
+    if selected.is_empty() {
+        return Err("No files matched the given patterns".to_string());
+    }
+
+    let source_name = args.source_name.clone().or_else(|| project.source_name.clone()).unwrap_or_else(|| {
+        root.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.to_string_lossy().to_string())
+    });
+
+    let options = generate_options(args, &project);
+
+    let out: Box<dyn Write> = match args.output.as_deref().filter(|p| *p != Path::new("-")) {
+        Some(path) => Box::new(BufWriter::new(
+            fs::File::create(path).map_err(|e| format!("Failed to create output file: {}", e))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let format = args.format.or(project.output.format).unwrap_or_default();
+    let mut writer = format.writer(out);
+
+    generate(writer.as_mut(), &selected, &source_name, &options)
+}
+
+/// Directories in the tree cut off by the depth limit
+fn count_truncated(nodes: &[FileNode]) -> usize {
+    nodes
+        .iter()
+        .map(|n| usize::from(n.truncated) + n.children.as_deref().map_or(0, count_truncated))
+        .sum()
+}
+
+/// Project defaults, with the command line flags switched on over them
+fn generate_options(args: &Args, project: &ProjectConfig) -> GenerateOptions {
+    let mut options = project.generate_options(GenerateOptions::default());
+    options.limits = Some(project.limits(FileLimits::default()));
+    options.line_numbers |= args.line_numbers;
+    options.minify |= args.minify;
+    if args.outline {
+        options.mode = InclusionMode::Outline;
+    }
+    options.git_diff = args.git_diff.clone();
+    options.git_log = args.git_log;
+    options
+}
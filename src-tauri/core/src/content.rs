@@ -0,0 +1,309 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A single line of a file body, or a marker for a range that was left out
+#[derive(Debug, Clone)]
+pub enum BodyLine {
+    /// Content line with its 1-based line number in the source file
+    Text { number: usize, text: String },
+    /// Range of source lines dropped from the output
+    Omitted { count: usize },
+}
+
+/// File content split into numbered lines so it can be truncated
+/// without losing track of where each line came from
+#[derive(Debug, Clone)]
+pub struct FileBody {
+    pub lines: Vec<BodyLine>,
+    /// Number of lines in the source file
+    pub total_lines: usize,
+    /// Whether the source file ended with a newline
+    pub trailing_newline: bool,
+}
+
+impl FileBody {
+    pub fn from_text(content: &str) -> Self {
+        let lines: Vec<BodyLine> = content
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(i, line)| BodyLine::Text {
+                number: i + 1,
+                text: line.strip_suffix('\n').unwrap_or(line).to_string(),
+            })
+            .collect();
+
+        Self {
+            total_lines: lines.len(),
+            lines,
+            trailing_newline: content.ends_with('\n'),
+        }
+    }
+
+    /// Number of content lines currently kept
+    pub fn line_count(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|l| matches!(l, BodyLine::Text { .. }))
+            .count()
+    }
+
+    /// Rough token estimate of the kept content
+    pub fn estimated_tokens(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|l| match l {
+                BodyLine::Text { text, .. } => estimate_tokens(text) + 1,
+                BodyLine::Omitted { .. } => 0,
+            })
+            .sum()
+    }
+
+    /// Size of the kept content in bytes, counting one newline per line
+    pub fn byte_len(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|l| match l {
+                BodyLine::Text { text, .. } => text.len() + 1,
+                BodyLine::Omitted { .. } => 0,
+            })
+            .sum()
+    }
+
+    /// Keep the first `head` and last `tail` lines, replacing everything in
+    /// between with a single omission marker
+    pub fn truncate_head_tail(&mut self, head: usize, tail: usize) {
+        let count = self.line_count();
+        if count <= head + tail {
+            return;
+        }
+
+        let mut kept = Vec::with_capacity(head + tail + 1);
+        let mut seen = 0;
+        let mut last_head_number = 0;
+        for line in self.lines.drain(..) {
+            match &line {
+                BodyLine::Text { number, .. } => {
+                    seen += 1;
+                    if seen <= head {
+                        last_head_number = *number;
+                        kept.push(line);
+                    } else if seen == count - tail + 1 {
+                        kept.push(BodyLine::Omitted { count: number - last_head_number - 1 });
+                        kept.push(line);
+                    } else if seen > count - tail {
+                        kept.push(line);
+                    }
+                }
+                // Markers inside the kept head or tail stay; the ones in
+                // between are counted by the middle marker
+                BodyLine::Omitted { .. } => {
+                    if seen < head || seen > count - tail {
+                        kept.push(line);
+                    }
+                }
+            }
+        }
+
+        if tail == 0 {
+            kept.push(BodyLine::Omitted { count: self.total_lines - last_head_number });
+        }
+
+        self.lines = kept;
+    }
+
+    /// Cut the body down to the given limits, keeping lines from the start.
+    /// The first line that does not fit is cut short, and everything after
+    /// it is replaced with a single omission marker.
+    pub fn truncate_to(&mut self, max_bytes: Option<u64>, max_lines: Option<usize>, max_tokens: Option<usize>) {
+        let mut bytes_left = max_bytes.map_or(usize::MAX, |max| usize::try_from(max).unwrap_or(usize::MAX));
+        let mut lines_left = max_lines.unwrap_or(usize::MAX);
+        let mut tokens_left = max_tokens.unwrap_or(usize::MAX);
+
+        let mut kept = Vec::with_capacity(self.lines.len());
+        // Source lines left out once the limits are reached
+        let mut omitted: Option<usize> = None;
+        for line in self.lines.drain(..) {
+            match (line, &mut omitted) {
+                (BodyLine::Text { .. }, Some(omitted)) => *omitted += 1,
+                (BodyLine::Omitted { count }, Some(omitted)) => *omitted += count,
+                (line @ BodyLine::Omitted { .. }, None) => kept.push(line),
+                (BodyLine::Text { number, text }, None) => {
+                    let bytes = text.len() + 1;
+                    let tokens = estimate_tokens(&text) + 1;
+                    if lines_left > 0 && bytes <= bytes_left && tokens <= tokens_left {
+                        lines_left -= 1;
+                        bytes_left -= bytes;
+                        tokens_left -= tokens;
+                        kept.push(BodyLine::Text { number, text });
+                        continue;
+                    }
+
+                    // Keep the part of the line that fits along with the marker,
+                    // sized for the longest possible count
+                    let marker_len = omitted_chars_marker(text.chars().count()).len();
+                    let max_chars = tokens_left.saturating_sub(1).saturating_mul(4).saturating_sub(marker_len);
+                    let max_bytes = bytes_left.saturating_sub(marker_len + 1);
+                    let cut = text
+                        .char_indices()
+                        .map(|(i, c)| i + c.len_utf8())
+                        .take(max_chars)
+                        .take_while(|&end| end <= max_bytes)
+                        .last()
+                        .filter(|_| lines_left > 0);
+                    match cut {
+                        Some(cut) => {
+                            let marker = omitted_chars_marker(text[cut..].chars().count());
+                            let text = format!("{}{}", &text[..cut], marker);
+                            kept.push(BodyLine::Text { number, text });
+                            omitted = Some(0);
+                        }
+                        None => omitted = Some(1),
+                    }
+                }
+            }
+        }
+        if let Some(count) = omitted.filter(|&count| count > 0) {
+            kept.push(BodyLine::Omitted { count });
+        }
+
+        self.lines = kept;
+    }
+
+    /// Render the body back to text, optionally prefixing each line with its
+    /// source line number padded to the width of the largest one
+    pub fn render(&self, line_numbers: bool) -> String {
+        let width = self.total_lines.to_string().len();
+        let mut out = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            match line {
+                BodyLine::Text { number, text } if line_numbers => {
+                    out.push_str(&format!("{:>width$} | {}", number, text, width = width));
+                }
+                BodyLine::Text { text, .. } => out.push_str(text),
+                BodyLine::Omitted { count } => {
+                    out.push_str(&format!("[... {} lines omitted ...]", count));
+                }
+            }
+        }
+        if self.trailing_newline || matches!(self.lines.last(), Some(BodyLine::Omitted { .. })) {
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Marker appended to a line cut short
+fn omitted_chars_marker(count: usize) -> String {
+    format!(" [... {} characters omitted ...]", count)
+}
+
+/// Rough token estimate (~4 characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Human readable file size for messages
+pub fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB {
+        format!("{}MB", bytes / MB)
+    } else if bytes >= KB {
+        format!("{}KB", bytes / KB)
+    } else {
+        format!("{}B", bytes)
+    }
+}
+
+/// Decode raw bytes as UTF-8, falling back to Latin-1 (ISO-8859-1)
+fn decode_with_fallback(bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// Try to read file as UTF-8, fallback to Latin-1, or return error message
+pub fn read_file_with_fallback(file_path: &str) -> Result<String, String> {
+    let path = Path::new(file_path);
+
+    // First try: UTF-8
+    match fs::read_to_string(path) {
+        Ok(content) => return Ok(content),
+        Err(e) => {
+            // If it's not a UTF-8 decode error, return immediately
+            if e.kind() != std::io::ErrorKind::InvalidData {
+                return Err(format!("Failed to read file: {}", e));
+            }
+        }
+    }
+
+    // Second try: Latin-1 (ISO-8859-1)
+    // Read as bytes and convert assuming Latin-1
+    match fs::read(path) {
+        Ok(bytes) => Ok(decode_with_fallback(bytes)),
+        Err(e) => Err(format!("Failed to read file as binary: {}", e)),
+    }
+}
+
+/// Stream a file keeping only its first `head` and last `tail` lines, so
+/// files far larger than the size limit never have to be held in memory.
+/// Each line is decoded on its own with the same UTF-8/Latin-1 fallback.
+pub fn read_head_tail(file_path: &str, head: usize, tail: usize) -> Result<FileBody, String> {
+    let file = fs::File::open(file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut reader = BufReader::new(file);
+
+    let mut head_lines = Vec::with_capacity(head);
+    let mut tail_lines: VecDeque<BodyLine> = VecDeque::with_capacity(tail + 1);
+    let mut total_lines = 0;
+    let mut trailing_newline = false;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        let read = reader
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+
+        total_lines += 1;
+        trailing_newline = buf.ends_with(b"\n");
+        if trailing_newline {
+            buf.pop();
+        }
+
+        let line = BodyLine::Text {
+            number: total_lines,
+            text: decode_with_fallback(std::mem::take(&mut buf)),
+        };
+        if head_lines.len() < head {
+            head_lines.push(line);
+        } else if tail > 0 {
+            if tail_lines.len() == tail {
+                tail_lines.pop_front();
+            }
+            tail_lines.push_back(line);
+        }
+    }
+
+    let mut body = FileBody {
+        lines: head_lines,
+        total_lines,
+        trailing_newline,
+    };
+    let omitted = total_lines - body.lines.len() - tail_lines.len();
+    if omitted > 0 {
+        body.lines.push(BodyLine::Omitted { count: omitted });
+    }
+    body.lines.extend(tail_lines);
+
+    Ok(body)
+}
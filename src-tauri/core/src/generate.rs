@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::limits::FileLimits;
+use crate::minify::{minify, Language};
+use crate::outline::{can_outline, outline, InclusionMode};
+use crate::git::{commit_log, unified_diff};
+use crate::content::{estimate_tokens, read_file_with_fallback, FileBody};
+use crate::writer::OutputWriter;
+
+/// Options controlling how the selected files are rendered
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerateOptions {
+    /// Per-file limits; `None` uses `FileLimits::default()`
+    pub limits: Option<FileLimits>,
+    /// Prefix every content line with its 1-based line number
+    pub line_numbers: bool,
+    /// Strip comments and blank-line runs from supported languages
+    pub minify: bool,
+    /// Inclusion mode for files without an entry in `file_modes`
+    pub mode: InclusionMode,
+    /// Per-file inclusion mode overrides, keyed by path
+    pub file_modes: HashMap<String, InclusionMode>,
+    /// Append a unified diff of the selected files against this ref (e.g. "HEAD")
+    pub git_diff: Option<String>,
+    /// Append the last N commit messages touching the selected files
+    pub git_log: Option<usize>,
+}
+
+/// Outcome of a single file in the generated output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileReport {
+    pub path: String,
+    /// Bytes removed by minification
+    pub bytes_saved: usize,
+    /// Whether only the outline of the file was included
+    pub outlined: bool,
+    /// Why the file content could not be included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Estimated token count of a single file as it would be rendered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEstimate {
+    pub path: String,
+    pub tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Token estimate of a whole selection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenEstimate {
+    pub files: Vec<FileEstimate>,
+    pub total_tokens: usize,
+}
+
+/// Counts the tokens of each file as `generate` renders it
+#[derive(Default)]
+struct TokenCounter {
+    files: Vec<FileEstimate>,
+}
+
+impl OutputWriter for TokenCounter {
+    fn write_header(&mut self, _source_name: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn write_file(&mut self, path: &str, _name: &str, content: Result<&str, &str>) -> Result<(), String> {
+        self.files.push(match content {
+            Ok(content) => FileEstimate { path: path.to_string(), tokens: estimate_tokens(content), error: None },
+            Err(e) => FileEstimate { path: path.to_string(), tokens: 0, error: Some(e.to_string()) },
+        });
+        Ok(())
+    }
+
+    fn write_section(&mut self, _title: &str, _content: Result<&str, &str>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Estimate the tokens of the selected files after limits, outline and
+/// minification are applied. Git sections are not counted.
+pub fn estimate_files(selected_paths: &[String], options: &GenerateOptions) -> Result<TokenEstimate, String> {
+    let options = GenerateOptions { git_diff: None, git_log: None, ..options.clone() };
+    let mut counter = TokenCounter::default();
+    generate(&mut counter, selected_paths, "", &options)?;
+    let total_tokens = counter.files.iter().map(|f| f.tokens).sum();
+    Ok(TokenEstimate { files: counter.files, total_tokens })
+}
+
+/// Write the header, the selected files and any git sections to `output`.
+/// Per-file failures are recorded in the returned reports; only write
+/// errors abort the generation.
+pub fn generate<O: OutputWriter + ?Sized>(
+    output: &mut O,
+    selected_paths: &[String],
+    source_name: &str,
+    options: &GenerateOptions,
+) -> Result<Vec<FileReport>, String> {
+    let limits = options.limits.clone().unwrap_or_default();
+
+    output.write_header(source_name)?;
+
+    let mut files = Vec::with_capacity(selected_paths.len());
+
+    // Process each selected file
+    for file_path in selected_paths {
+        let path = Path::new(file_path);
+
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(file_path);
+
+        // Read file with encoding fallback, outline and minify, then enforce the size limits
+        let mode = options.file_modes.get(file_path).copied().unwrap_or(options.mode);
+        let mut bytes_saved = 0;
+        let mut outlined = false;
+        // Outlines are parsed from the whole file and cut to the limits
+        // afterwards; a head/tail read would leave nothing to outline
+        let read = if mode == InclusionMode::Outline && can_outline(path) {
+            read_file_with_fallback(file_path).map(|content| FileBody::from_text(&content))
+        } else {
+            limits.read(file_path)
+        };
+        let body = read.and_then(|mut body| {
+            if mode == InclusionMode::Outline {
+                outlined = outline(&mut body, path)?;
+            }
+            if let Some(lang) = Language::from_path(path).filter(|_| options.minify) {
+                bytes_saved = minify(&mut body, lang);
+            }
+            limits.apply(&mut body)?;
+            Ok(body)
+        });
+
+        let error = match body {
+            Ok(body) => {
+                output.write_file(file_path, file_name, Ok(&body.render(options.line_numbers)))?;
+                None
+            }
+            Err(e) => {
+                output.write_file(file_path, file_name, Err(&e))?;
+                Some(e)
+            }
+        };
+
+        files.push(FileReport { path: file_path.clone(), bytes_saved, outlined, error });
+    }
+
+    if let Some(reference) = &options.git_diff {
+        let diff = unified_diff(selected_paths, reference)
+            .map_err(|e| format!("unable to read git diff - {}", e));
+        output.write_section(&format!("GIT DIFF: {}", reference), diff.as_deref().map_err(|e| e.as_str()))?;
+    }
+
+    if let Some(count) = options.git_log {
+        let log = commit_log(selected_paths, count)
+            .map_err(|e| format!("unable to read git log - {}", e));
+        output.write_section(&format!("GIT LOG: last {} commits", count), log.as_deref().map_err(|e| e.as_str()))?;
+    }
+
+    output.finish()?;
+    Ok(files)
+}
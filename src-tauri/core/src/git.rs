@@ -0,0 +1,224 @@
+use git2::{Delta, Diff, DiffFormat, DiffOptions, Repository, Sort};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Which set of changes to look up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GitChangeSet {
+    /// Unstaged modifications and untracked files in the working tree
+    WorkingTree,
+    /// Changes staged in the index
+    Staged,
+    /// Everything that differs from the given ref (branch, tag or commit),
+    /// including uncommitted changes
+    AgainstRef { reference: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedFile {
+    /// Absolute path, in the same form `scan_directory` returns
+    pub path: String,
+    pub status: String,
+}
+
+/// Open the repository containing `dir_path`. Returns the repository and
+/// its canonical working directory.
+fn open_repository(dir_path: &str) -> Result<(Repository, PathBuf), String> {
+    let repo = Repository::discover(dir_path)
+        .map_err(|e| format!("Not a git repository: {}", e.message()))?;
+    let workdir = repo
+        .workdir()
+        .ok_or("Bare repositories are not supported")?
+        .canonicalize()
+        .map_err(|e| format!("Failed to canonicalize path: {}", e))?;
+    Ok((repo, workdir))
+}
+
+/// Build the diff for a change set, restricted to `pathspecs` when given
+fn diff_for<'r>(
+    repo: &'r Repository,
+    change_set: &GitChangeSet,
+    pathspecs: &[String],
+) -> Result<Diff<'r>, String> {
+    let mut opts = DiffOptions::new();
+    for spec in pathspecs {
+        opts.pathspec(spec);
+    }
+    if !pathspecs.is_empty() {
+        opts.disable_pathspec_match(true).show_untracked_content(true);
+    }
+
+    let head_tree = || -> Result<Option<git2::Tree<'r>>, String> {
+        match repo.head() {
+            Ok(head) => head
+                .peel_to_tree()
+                .map(Some)
+                .map_err(|e| format!("Failed to read HEAD: {}", e.message())),
+            // Unborn branch: nothing committed yet
+            Err(_) => Ok(None),
+        }
+    };
+
+    let mut diff = match change_set {
+        GitChangeSet::WorkingTree => {
+            opts.include_untracked(true).recurse_untracked_dirs(true);
+            repo.diff_index_to_workdir(None, Some(&mut opts))
+        }
+        GitChangeSet::Staged => {
+            let tree = head_tree()?;
+            repo.diff_tree_to_index(tree.as_ref(), None, Some(&mut opts))
+        }
+        GitChangeSet::AgainstRef { reference } => {
+            let tree = repo
+                .revparse_single(reference)
+                .and_then(|obj| obj.peel_to_tree())
+                .map_err(|e| format!("Unknown ref '{}': {}", reference, e.message()))?;
+            opts.include_untracked(true).recurse_untracked_dirs(true);
+            repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut opts))
+        }
+    }
+    .map_err(|e| format!("Failed to compute diff: {}", e.message()))?;
+
+    diff.find_similar(None)
+        .map_err(|e| format!("Failed to detect renames: {}", e.message()))?;
+    Ok(diff)
+}
+
+fn status_name(delta: Delta) -> &'static str {
+    match delta {
+        Delta::Added => "added",
+        Delta::Deleted => "deleted",
+        Delta::Modified => "modified",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        Delta::Untracked => "untracked",
+        Delta::Typechange => "typechange",
+        Delta::Conflicted => "conflicted",
+        _ => "unmodified",
+    }
+}
+
+/// List files changed in the git repository containing `dir_path`, limited
+/// to files inside `dir_path`. Reads the repository locally, no remotes are
+/// contacted.
+pub fn changed_files(dir_path: &str, change_set: &GitChangeSet) -> Result<Vec<ChangedFile>, String> {
+    let root = Path::new(dir_path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to canonicalize path: {}", e))?;
+    let (repo, workdir) = open_repository(dir_path)?;
+    let diff = diff_for(&repo, change_set, &[])?;
+
+    let mut files: Vec<ChangedFile> = diff
+        .deltas()
+        .filter_map(|delta| {
+            let relative = delta.new_file().path().or_else(|| delta.old_file().path())?;
+            let path = workdir.join(relative);
+            path.starts_with(&root).then(|| ChangedFile {
+                path: path.to_string_lossy().to_string(),
+                status: status_name(delta.status()).to_string(),
+            })
+        })
+        .collect();
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files.dedup_by(|a, b| a.path == b.path);
+    Ok(files)
+}
+
+/// Open the repository holding the selected files and turn their paths into
+/// workdir-relative pathspecs. Files outside the repository are ignored.
+fn open_for_paths(paths: &[String]) -> Result<(Repository, Vec<String>), String> {
+    let first = paths
+        .first()
+        .and_then(|p| Path::new(p).parent())
+        .ok_or("No files selected")?;
+    let (repo, workdir) = open_repository(&first.to_string_lossy())?;
+
+    let pathspecs = paths
+        .iter()
+        .filter_map(|p| {
+            let path = Path::new(p).canonicalize().ok()?;
+            let relative = path.strip_prefix(&workdir).ok()?;
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    Ok((repo, pathspecs))
+}
+
+/// Unified diff of the given files against `reference`, including
+/// uncommitted changes
+pub(crate) fn unified_diff(paths: &[String], reference: &str) -> Result<String, String> {
+    let (repo, pathspecs) = open_for_paths(paths)?;
+    if pathspecs.is_empty() {
+        return Ok(String::new());
+    }
+    let change_set = GitChangeSet::AgainstRef { reference: reference.to_string() };
+    let diff = diff_for(&repo, &change_set, &pathspecs)?;
+
+    let mut out = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            out.push(line.origin());
+        }
+        out.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .map_err(|e| format!("Failed to render diff: {}", e.message()))?;
+    Ok(out)
+}
+
+/// The last `count` commits reachable from HEAD that touch any of the given files
+pub(crate) fn commit_log(paths: &[String], count: usize) -> Result<String, String> {
+    let (repo, pathspecs) = open_for_paths(paths)?;
+    if pathspecs.is_empty() || count == 0 {
+        return Ok(String::new());
+    }
+
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e.message()))?;
+    walk.set_sorting(Sort::TIME)
+        .and_then(|_| walk.push_head())
+        .map_err(|e| format!("Failed to walk history: {}", e.message()))?;
+
+    let mut opts = DiffOptions::new();
+    for spec in &pathspecs {
+        opts.pathspec(spec);
+    }
+    opts.disable_pathspec_match(true);
+
+    let mut out = String::new();
+    let mut found = 0;
+    for oid in walk {
+        if found == count {
+            break;
+        }
+        let commit = oid
+            .and_then(|oid| repo.find_commit(oid))
+            .map_err(|e| format!("Failed to read commit: {}", e.message()))?;
+        let tree = commit.tree().ok();
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let touches = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), tree.as_ref(), Some(&mut opts))
+            .map(|d| d.deltas().len() > 0)
+            .unwrap_or(false);
+        if !touches {
+            continue;
+        }
+
+        found += 1;
+        let author = commit.author();
+        out.push_str(&format!(
+            "commit {}\nAuthor: {} <{}>\n\n",
+            commit.id(),
+            author.name().unwrap_or(""),
+            author.email().unwrap_or("")
+        ));
+        for line in commit.message().unwrap_or("").trim_end().lines() {
+            out.push_str(&format!("    {}\n", line));
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
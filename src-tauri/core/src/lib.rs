@@ -0,0 +1,67 @@
+//! Scanning, reading and bundling logic behind File Extractor, usable
+//! without the desktop app.
+//!
+//! ```no_run
+//! use file_extractor_core::{generate, scan_tree, select_files, GenerateOptions, PathFilter, ScanOptions, TextWriter};
+//!
+//! let root = std::path::Path::new("/path/to/project").canonicalize().unwrap();
+//! let tree = scan_tree(&root.to_string_lossy(), &ScanOptions::default()).unwrap();
+//! let filter = PathFilter::new(&["src/**/*.rs".to_string()], &[]).unwrap();
+//! let files = select_files(&tree, &root, &filter);
+//!
+//! let mut writer = TextWriter::new(std::io::stdout());
+//! generate(&mut writer, &files, "my-project", &GenerateOptions::default()).unwrap();
+//! ```
+
+mod content;
+mod generate;
+mod git;
+mod limits;
+mod store;
+mod minify;
+mod outline;
+mod patterns;
+mod project;
+mod scan;
+#[cfg(feature = "mcp")]
+mod mcp;
+#[cfg(feature = "server")]
+mod server;
+mod settings;
+mod storage;
+mod transfer;
+#[cfg(feature = "watch")]
+mod watch;
+mod writer;
+
+pub use content::{estimate_tokens, read_file_with_fallback};
+pub use generate::{estimate_files, generate, FileEstimate, FileReport, GenerateOptions, TokenEstimate};
+pub use git::{changed_files, ChangedFile, GitChangeSet};
+pub use limits::{FileLimits, TruncationMode, DEFAULT_MAX_FILE_BYTES};
+pub use outline::InclusionMode;
+pub use patterns::{select_files, PathFilter, SelectionPatterns};
+pub use project::{
+    common_ancestor, discover_project_config, ProjectConfig, ProjectLimits, ProjectOutput,
+    ProjectScan, PROJECT_CONFIG_FILE,
+};
+pub use scan::{
+    list_directory, scan_tree, scan_tree_streaming, DirListing, FileNode, ScanOptions,
+    SymlinkPolicy, DEFAULT_HIDDEN_ALLOWLIST, DEFAULT_MAX_DEPTH,
+};
+#[cfg(feature = "mcp")]
+pub use mcp::run_stdio_server;
+#[cfg(feature = "server")]
+pub use server::{generate_token, ApiServer, ShutdownHandle};
+pub use settings::{
+    create_preset, delete_preset, list_presets, load_preset, load_selection_history,
+    load_selections, load_settings, prune_selections, recent_folders, rename_preset,
+    resolve_generate_options, resolve_scan_options, save_selections, save_settings, update_preset, ApiServerSettings,
+    RecentFolder, SelectionPreset, SettingsData, SourceSelection, DEFAULT_SERVER_PORT,
+};
+pub use storage::{config_dir, data_dir, CONFIG_DIR_ENV};
+pub use transfer::{
+    export_settings, import_settings, ImportConflict, ImportMode, ImportReport, SettingsExport,
+};
+#[cfg(feature = "watch")]
+pub use watch::{FsChange, TreeWatcher};
+pub use writer::{OutputFormat, OutputWriter, TextWriter};
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+use crate::content::{format_size, read_file_with_fallback, read_head_tail, FileBody};
+
+/// Default per-file byte limit (matches the previous hardcoded limit)
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 50 * 1024 * 1024; // 50MB
+
+/// What to do with a file that exceeds one of the limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum TruncationMode {
+    /// Skip the file and write an error line instead of its content
+    Reject,
+    /// Keep the first `head` and last `tail` lines with an omission marker in between
+    HeadTail { head: usize, tail: usize },
+}
+
+/// Per-file size limits applied during generation. A `None` limit is not enforced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileLimits {
+    pub max_bytes: Option<u64>,
+    pub max_lines: Option<usize>,
+    pub max_tokens: Option<usize>,
+    pub truncation: TruncationMode,
+}
+
+impl Default for FileLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: Some(DEFAULT_MAX_FILE_BYTES),
+            max_lines: None,
+            max_tokens: None,
+            truncation: TruncationMode::Reject,
+        }
+    }
+}
+
+impl FileLimits {
+    /// Read a file honoring the byte limit. Oversized files are either
+    /// rejected or streamed keeping only their head and tail.
+    pub fn read(&self, file_path: &str) -> Result<FileBody, String> {
+        let size = std::fs::metadata(file_path)
+            .map_err(|e| format!("Failed to read file metadata: {}", e))?
+            .len();
+
+        if let Some(max_bytes) = self.max_bytes.filter(|&max| size > max) {
+            return match self.truncation {
+                TruncationMode::Reject => Err(format!(
+                    "File too large: {} (max: {})",
+                    format_size(size),
+                    format_size(max_bytes)
+                )),
+                TruncationMode::HeadTail { head, tail } => read_head_tail(file_path, head, tail),
+            };
+        }
+
+        let content = read_file_with_fallback(file_path)?;
+        Ok(FileBody::from_text(&content))
+    }
+
+    /// Enforce the limits on an already loaded body. With head/tail
+    /// truncation, a body whose kept lines are still too large (e.g. a
+    /// minified file on a single line) is cut down to the limits.
+    pub fn apply(&self, body: &mut FileBody) -> Result<(), String> {
+        let Some(exceeded) = self.exceeded(body) else {
+            return Ok(());
+        };
+
+        match self.truncation {
+            TruncationMode::Reject => Err(exceeded),
+            TruncationMode::HeadTail { head, tail } => {
+                body.truncate_head_tail(head, tail);
+                if self.exceeded(body).is_some() {
+                    body.truncate_to(self.max_bytes, self.max_lines, self.max_tokens);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Message for the first limit the body is over, if any
+    fn exceeded(&self, body: &FileBody) -> Option<String> {
+        let lines = body.line_count();
+        let bytes = body.byte_len() as u64;
+        let tokens = body.estimated_tokens();
+
+        let lines_over = self
+            .max_lines
+            .filter(|&max| lines > max)
+            .map(|max| format!("File too long: {} lines (max: {})", lines, max));
+        let bytes_over = || {
+            self.max_bytes
+                .filter(|&max| bytes > max)
+                .map(|max| format!("File too large: {} (max: {})", format_size(bytes), format_size(max)))
+        };
+        let tokens_over = || {
+            self.max_tokens
+                .filter(|&max| tokens > max)
+                .map(|max| format!("File too long: ~{} tokens (max: {})", tokens, max))
+        };
+        lines_over.or_else(bytes_over).or_else(tokens_over)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::BodyLine;
+
+    fn head_tail(head: usize, tail: usize) -> FileLimits {
+        FileLimits {
+            max_bytes: Some(1000),
+            max_lines: None,
+            max_tokens: Some(100),
+            truncation: TruncationMode::HeadTail { head, tail },
+        }
+    }
+
+    fn numbered(count: usize) -> FileBody {
+        let text: String = (1..=count).map(|i| format!("line {}\n", i)).collect();
+        FileBody::from_text(&text)
+    }
+
+    #[test]
+    fn single_long_line_is_cut_to_the_limits() {
+        let mut body = FileBody::from_text(&format!("{}\n", "x".repeat(100_000)));
+        head_tail(3, 2).apply(&mut body).unwrap();
+
+        assert!(head_tail(3, 2).exceeded(&body).is_none());
+        let rendered = body.render(false);
+        assert!(rendered.starts_with("xxxx"));
+        assert!(rendered.contains("characters omitted ...]"));
+        assert!(rendered.len() <= 1000);
+    }
+
+    #[test]
+    fn long_lines_in_head_and_tail_are_cut() {
+        let line = "y".repeat(300);
+        let text: String = (0..20).map(|_| format!("{}\n", line)).collect();
+        let mut body = FileBody::from_text(&text);
+        head_tail(3, 2).apply(&mut body).unwrap();
+
+        assert!(head_tail(3, 2).exceeded(&body).is_none());
+        assert_eq!(body.line_count(), 2);
+        match body.lines.last() {
+            Some(BodyLine::Omitted { count }) => assert_eq!(*count, 15 + 3),
+            other => panic!("expected an omission marker, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reject_still_fails_over_the_limits() {
+        let limits = FileLimits { truncation: TruncationMode::Reject, ..head_tail(0, 0) };
+        let mut body = FileBody::from_text(&"z".repeat(5000));
+        assert!(limits.apply(&mut body).unwrap_err().starts_with("File too large"));
+    }
+
+    #[test]
+    fn omission_marker_counts_the_middle_lines() {
+        let mut body = numbered(10);
+        body.truncate_head_tail(3, 2);
+        assert_eq!(body.render(true), " 1 | line 1\n 2 | line 2\n 3 | line 3\n[... 5 lines omitted ...]\n 9 | line 9\n10 | line 10\n");
+    }
+
+    #[test]
+    fn tail_zero_keeps_only_the_head() {
+        let mut body = numbered(10);
+        body.truncate_head_tail(4, 0);
+        assert_eq!(body.render(false), "line 1\nline 2\nline 3\nline 4\n[... 6 lines omitted ...]\n");
+    }
+
+    #[test]
+    fn head_zero_keeps_only_the_tail() {
+        let mut body = numbered(10);
+        body.truncate_head_tail(0, 3);
+        assert_eq!(body.render(false), "[... 7 lines omitted ...]\nline 8\nline 9\nline 10\n");
+    }
+
+    #[test]
+    fn markers_inside_the_kept_lines_stay() {
+        let mut body = numbered(20);
+        // Lines 3-5 already left out, e.g. by the outline
+        body.lines.splice(2..5, [BodyLine::Omitted { count: 3 }]);
+        body.truncate_head_tail(4, 2);
+        assert_eq!(
+            body.render(false),
+            "line 1\nline 2\n[... 3 lines omitted ...]\nline 6\nline 7\n[... 11 lines omitted ...]\nline 19\nline 20\n"
+        );
+    }
+
+    #[test]
+    fn short_body_is_left_alone() {
+        let mut body = numbered(5);
+        body.truncate_head_tail(3, 2);
+        assert_eq!(body.line_count(), 5);
+        assert!(body.lines.iter().all(|l| matches!(l, BodyLine::Text { .. })));
+    }
+}
@@ -0,0 +1,344 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use crate::generate::{estimate_files, generate, GenerateOptions};
+use crate::scan::{scan_tree, ScanOptions, SymlinkPolicy};
+use crate::settings::{load_selection_history, resolve_generate_options, resolve_scan_options};
+use crate::writer::TextWriter;
+
+/// Protocol revisions this server speaks, newest first
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const SELECTION_URI_PREFIX: &str = "file-extractor://selections/";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const RESOURCE_NOT_FOUND: i64 = -32002;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+#[derive(Deserialize)]
+struct ListTreeArgs {
+    dir_path: String,
+    /// Override the settings and project defaults when given
+    max_depth: Option<usize>,
+    symlinks: Option<SymlinkPolicy>,
+    include_hidden: Option<bool>,
+}
+
+impl ListTreeArgs {
+    fn scan_options(&self) -> Result<ScanOptions, String> {
+        let mut options = resolve_scan_options(&self.dir_path)?;
+        if let Some(max_depth) = self.max_depth {
+            options.max_depth = max_depth;
+        }
+        if let Some(symlinks) = self.symlinks {
+            options.symlinks = symlinks;
+        }
+        if let Some(include_hidden) = self.include_hidden {
+            options.include_hidden = include_hidden;
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Deserialize)]
+struct ReadFileArgs {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct BundleArgs {
+    paths: Vec<String>,
+    #[serde(default)]
+    source_name: String,
+    /// Project defaults and the settings limits apply when omitted
+    options: Option<GenerateOptions>,
+}
+
+/// Serve the Model Context Protocol over stdin/stdout, one JSON-RPC message
+/// per line, until stdin is closed. Logs go to stderr.
+pub fn run_stdio_server() -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| format!("Failed to read from stdin: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_message(&line) {
+            writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .map_err(|e| format!("Failed to write to stdout: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Handle one incoming message; notifications get no response
+fn handle_message(line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+    };
+
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        // Responses to our (nonexistent) requests are ignored
+        return id.map(|id| error_response(id, RpcError::new(INVALID_REQUEST, "Missing method")));
+    };
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = dispatch(method, params);
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn dispatch(method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(params),
+        "resources/list" => list_resources(),
+        "resources/read" => read_resource(&params),
+        _ if method.starts_with("notifications/") => Ok(Value::Null),
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    }
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {}, "resources": {} },
+        "serverInfo": { "name": "file-extractor", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn tool_definitions() -> Value {
+    let options_schema = json!({
+        "type": "object",
+        "description": "Generation options: line_numbers, minify, mode (full|outline), limits, git_diff, git_log",
+    });
+    json!([
+        {
+            "name": "list_tree",
+            "description": "List the files and folders under a directory, skipping dependency and build folders",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir_path": { "type": "string", "description": "Absolute path of the directory" },
+                    "max_depth": { "type": "integer", "description": "Maximum depth to descend into" },
+                    "symlinks": {
+                        "type": "string",
+                        "enum": ["skip", "follow_within_root", "follow_all"],
+                        "description": "Which symbolic links to list",
+                    },
+                    "include_hidden": { "type": "boolean", "description": "Also list dotfiles and dot-directories" },
+                },
+                "required": ["dir_path"],
+            },
+        },
+        {
+            "name": "read_file",
+            "description": "Read a text file, decoding it as UTF-8 or Latin-1, within the configured size limits",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string", "description": "Absolute path of the file" } },
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "estimate_tokens",
+            "description": "Estimate the tokens each file would take in a bundle",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "paths": { "type": "array", "items": { "type": "string" } },
+                    "options": options_schema,
+                },
+                "required": ["paths"],
+            },
+        },
+        {
+            "name": "generate_bundle",
+            "description": "Bundle the given files into a single context document",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "paths": { "type": "array", "items": { "type": "string" } },
+                    "source_name": { "type": "string", "description": "Name written in the bundle header" },
+                    "options": options_schema,
+                },
+                "required": ["paths"],
+            },
+        },
+    ])
+}
+
+fn call_tool(params: Value) -> Result<Value, RpcError> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing tool name"))?;
+    let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+    let output = match name {
+        "list_tree" => parse_args(arguments).and_then(|args: ListTreeArgs| {
+            let tree = scan_tree(&args.dir_path, &args.scan_options()?)?;
+            serde_json::to_string_pretty(&tree).map_err(|e| e.to_string())
+        }),
+        "read_file" => parse_args(arguments).and_then(|args: ReadFileArgs| read_file(&args.path)),
+        "estimate_tokens" => parse_args(arguments).and_then(|args: BundleArgs| {
+            let estimate = estimate_files(&args.paths, &resolve_generate_options(&args.paths, args.options)?)?;
+            serde_json::to_string_pretty(&estimate).map_err(|e| e.to_string())
+        }),
+        "generate_bundle" => parse_args(arguments)
+            .and_then(|args: BundleArgs| bundle(&args.paths, &args.source_name, args.options)),
+        _ => return Err(RpcError::new(INVALID_PARAMS, format!("Unknown tool: {}", name))),
+    };
+
+    // Tool failures are reported to the model, not as protocol errors
+    Ok(match output {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+    })
+}
+
+fn parse_args<T: DeserializeOwned>(arguments: Value) -> Result<T, String> {
+    serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {}", e))
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    let limits = resolve_generate_options(&[path.to_string()], None)?.limits.unwrap_or_default();
+    let mut body = limits.read(path)?;
+    limits.apply(&mut body)?;
+    Ok(body.render(false))
+}
+
+fn bundle(paths: &[String], source_name: &str, options: Option<GenerateOptions>) -> Result<String, String> {
+    let mut writer = TextWriter::new(Vec::new());
+    generate(&mut writer, paths, source_name, &resolve_generate_options(paths, options)?)?;
+    Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+fn folder_name(folder: &str) -> String {
+    Path::new(folder)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| folder.to_string())
+}
+
+/// Saved folder selections and their presets, each readable as a bundle
+fn list_resources() -> Result<Value, RpcError> {
+    let history = load_selection_history().map_err(|e| RpcError::new(INVALID_REQUEST, e))?;
+    let mut resources = Vec::new();
+    for (folder, selection) in &history {
+        let uri = format!("{}{}", SELECTION_URI_PREFIX, percent_encode(folder));
+        resources.push(json!({
+            "uri": uri,
+            "name": folder_name(folder),
+            "description": format!("Bundle of the {} files last selected in {}", selection.files.len(), folder),
+            "mimeType": "text/plain",
+        }));
+        for preset in &selection.presets {
+            resources.push(json!({
+                "uri": format!("{}?preset={}", uri, percent_encode(&preset.name)),
+                "name": format!("{}: {}", folder_name(folder), preset.name),
+                "description": match &preset.patterns {
+                    Some(patterns) => format!("Bundle of the files matching {:?} in preset '{}' of {}", patterns.include, preset.name, folder),
+                    None => format!("Bundle of the {} files in preset '{}' of {}", preset.files.len(), preset.name, folder),
+                },
+                "mimeType": "text/plain",
+            }));
+        }
+    }
+    Ok(json!({ "resources": resources }))
+}
+
+fn read_resource(params: &Value) -> Result<Value, RpcError> {
+    let uri = params
+        .get("uri")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing resource uri"))?;
+    let not_found = || RpcError::new(RESOURCE_NOT_FOUND, format!("Resource not found: {}", uri));
+
+    let rest = uri.strip_prefix(SELECTION_URI_PREFIX).ok_or_else(not_found)?;
+    let (folder, preset) = match rest.split_once("?preset=") {
+        Some((folder, preset)) => (folder, Some(percent_decode(preset).ok_or_else(not_found)?)),
+        None => (rest, None),
+    };
+    let folder = percent_decode(folder).ok_or_else(not_found)?;
+
+    let history = load_selection_history().map_err(|e| RpcError::new(INVALID_REQUEST, e))?;
+    let selection = history.get(&folder).ok_or_else(not_found)?;
+    let files = match &preset {
+        Some(name) => {
+            let preset = selection.presets.iter().find(|p| &p.name == name).ok_or_else(not_found)?;
+            resolve_scan_options(&folder)
+                .and_then(|options| preset.resolve_files(&folder, &options))
+                .map_err(|e| RpcError::new(INVALID_REQUEST, e))?
+        }
+        None => selection.files.clone(),
+    };
+
+    let text = bundle(&files, &folder_name(&folder), None)
+        .map_err(|e| RpcError::new(INVALID_REQUEST, e))?;
+    Ok(json!({ "contents": [{ "uri": uri, "mimeType": "text/plain", "text": text }] }))
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
@@ -0,0 +1,715 @@
+use std::path::Path;
+
+use crate::content::{BodyLine, FileBody};
+
+/// Comment syntaxes understood by the minifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    JavaScript,
+    Python,
+    CFamily,
+    Shell,
+}
+
+impl Language {
+    /// Detect the language from the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        let lang = match ext.as_str() {
+            "rs" => Language::Rust,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Language::JavaScript,
+            "py" | "pyw" | "pyi" => Language::Python,
+            "c" | "h" | "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" | "java" | "cs" | "go"
+            | "kt" | "kts" | "swift" | "scala" => Language::CFamily,
+            "sh" | "bash" | "zsh" | "ksh" | "fish" => Language::Shell,
+            _ => return None,
+        };
+        Some(lang)
+    }
+}
+
+/// Strip comments, license banners and runs of blank lines from a file body.
+/// Returns the number of bytes saved. Lines keep their original numbers and
+/// string literals are copied verbatim.
+pub fn minify(body: &mut FileBody, lang: Language) -> usize {
+    let before = body.render(false).len();
+
+    let mut lines = Vec::with_capacity(body.lines.len());
+    let mut segment: Vec<(usize, String)> = Vec::new();
+    for line in body.lines.drain(..) {
+        match line {
+            BodyLine::Text { number, text } => segment.push((number, text)),
+            omitted @ BodyLine::Omitted { .. } => {
+                minify_segment(std::mem::take(&mut segment), lang, &mut lines);
+                lines.push(omitted);
+            }
+        }
+    }
+    minify_segment(segment, lang, &mut lines);
+
+    body.lines = lines;
+    before.saturating_sub(body.render(false).len())
+}
+
+/// Minify a run of consecutive source lines
+fn minify_segment(segment: Vec<(usize, String)>, lang: Language, out: &mut Vec<BodyLine>) {
+    if segment.is_empty() {
+        return;
+    }
+
+    let source = segment
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let stripped = strip_comments(&source, lang);
+
+    let start = out.len();
+    let mut previous_blank = true;
+    for (i, ((number, original), text)) in segment
+        .into_iter()
+        .zip(stripped.text.split('\n'))
+        .enumerate()
+    {
+        let text = if stripped.removed[i] {
+            // Drop lines that only held comments
+            if text.trim().is_empty() {
+                continue;
+            }
+            text.trim_end().to_string()
+        } else {
+            original
+        };
+
+        // Collapse runs of blank lines, but never inside a multi-line literal
+        let blank = text.trim().is_empty();
+        if blank && previous_blank && !stripped.in_literal[i] {
+            continue;
+        }
+        previous_blank = blank && !stripped.in_literal[i];
+
+        out.push(BodyLine::Text { number, text });
+    }
+
+    // Drop trailing blank lines left behind by removed comments
+    while out.len() > start
+        && matches!(out.last(), Some(BodyLine::Text { text, .. }) if text.trim().is_empty())
+    {
+        out.pop();
+    }
+}
+
+/// Result of a comment-stripping pass. The text keeps every newline of the
+/// source so lines map one-to-one.
+struct Stripped {
+    text: String,
+    /// Per line: whether any comment characters were removed
+    removed: Vec<bool>,
+    /// Per line: whether the line starts inside a string literal or heredoc
+    in_literal: Vec<bool>,
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+struct Scanner {
+    chars: Vec<char>,
+    pos: usize,
+    out: String,
+    line: usize,
+    removed: Vec<bool>,
+    in_literal: Vec<bool>,
+}
+
+impl Scanner {
+    fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            pos: 0,
+            out: String::with_capacity(source.len()),
+            line: 0,
+            removed: vec![false],
+            in_literal: vec![false],
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn prev(&self) -> Option<char> {
+        self.pos.checked_sub(1).map(|i| self.chars[i])
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c))
+    }
+
+    fn emit(&mut self, c: char, literal: bool) {
+        self.out.push(c);
+        if c == '\n' {
+            self.line += 1;
+            self.removed.push(false);
+            self.in_literal.push(literal);
+        }
+    }
+
+    /// Copy the current character to the output
+    fn copy(&mut self, literal: bool) {
+        if let Some(c) = self.peek(0) {
+            self.emit(c, literal);
+            self.pos += 1;
+        }
+    }
+
+    fn copy_n(&mut self, n: usize, literal: bool) {
+        for _ in 0..n {
+            self.copy(literal);
+        }
+    }
+
+    /// Drop the current character, keeping newlines so lines stay aligned
+    fn skip(&mut self) {
+        match self.peek(0) {
+            Some('\n') => self.emit('\n', false),
+            Some(_) => self.removed[self.line] = true,
+            None => return,
+        }
+        self.pos += 1;
+    }
+
+    /// Skip a comment running to the end of the line
+    fn skip_line_comment(&mut self) {
+        while self.peek(0).is_some_and(|c| c != '\n') {
+            self.skip();
+        }
+    }
+
+    /// Skip a block comment, optionally allowing nesting (Rust)
+    fn skip_block_comment(&mut self, nested: bool) {
+        let mut depth = 0;
+        while self.peek(0).is_some() {
+            if self.starts_with("/*") {
+                depth += 1;
+                self.skip();
+                self.skip();
+            } else if self.starts_with("*/") {
+                depth -= 1;
+                self.skip();
+                self.skip();
+                if depth == 0 || !nested {
+                    return;
+                }
+            } else {
+                self.skip();
+            }
+        }
+    }
+
+    /// Copy a quoted literal starting at the opening delimiter. With
+    /// `escapes` a backslash protects the next character; single-line
+    /// literals stop at an unescaped newline.
+    fn copy_quoted(&mut self, open: &str, close: &str, escapes: bool, multiline: bool) {
+        self.copy_n(open.chars().count(), false);
+        while let Some(c) = self.peek(0) {
+            if escapes && c == '\\' {
+                self.copy_n(2, true);
+            } else if self.starts_with(close) {
+                self.copy_n(close.chars().count(), true);
+                return;
+            } else if c == '\n' && !multiline {
+                return;
+            } else {
+                self.copy(true);
+            }
+        }
+    }
+
+    /// Copy the current line up to (not including) its newline
+    fn copy_line(&mut self, literal: bool) {
+        while self.peek(0).is_some_and(|c| c != '\n') {
+            self.copy(literal);
+        }
+    }
+
+    /// Last non-whitespace character already written
+    fn last_significant(&self) -> Option<char> {
+        self.out.chars().rev().find(|c| !c.is_whitespace())
+    }
+
+    fn finish(self) -> Stripped {
+        Stripped {
+            text: self.out,
+            removed: self.removed,
+            in_literal: self.in_literal,
+        }
+    }
+}
+
+fn strip_comments(source: &str, lang: Language) -> Stripped {
+    let mut s = Scanner::new(source);
+    match lang {
+        Language::Rust => scan_rust(&mut s),
+        Language::JavaScript => scan_javascript(&mut s),
+        Language::CFamily => scan_c_family(&mut s),
+        Language::Python => scan_python(&mut s),
+        Language::Shell => scan_shell(&mut s),
+    }
+    s.finish()
+}
+
+fn scan_rust(s: &mut Scanner) {
+    while let Some(c) = s.peek(0) {
+        if s.starts_with("//") {
+            s.skip_line_comment();
+        } else if s.starts_with("/*") {
+            s.skip_block_comment(true);
+        } else if c == '"' {
+            s.copy_quoted("\"", "\"", true, true);
+        } else if c == '\'' {
+            // Char literal or lifetime/label
+            if s.peek(1) == Some('\\') {
+                s.copy_quoted("'", "'", true, false);
+            } else if s.peek(2) == Some('\'') {
+                s.copy_n(3, false);
+            } else {
+                s.copy(false);
+            }
+        } else if matches!(c, 'r' | 'b' | 'c') && !s.prev().is_some_and(is_ident) {
+            // Raw strings: r"..", r#".."#, br"..", cr".."
+            let r = if c == 'r' { 0 } else { 1 };
+            let hashes = (r + 1..)
+                .take_while(|&i| s.peek(i) == Some('#'))
+                .count();
+            if s.peek(r) == Some('r') && s.peek(r + 1 + hashes) == Some('"') {
+                let open: String = s.chars[s.pos..s.pos + r + 2 + hashes].iter().collect();
+                let close = format!("\"{}", "#".repeat(hashes));
+                s.copy_quoted(&open, &close, false, true);
+            } else {
+                // Copy the whole identifier so its tail is not re-examined
+                while s.peek(0).is_some_and(is_ident) {
+                    s.copy(false);
+                }
+            }
+        } else if is_ident(c) {
+            while s.peek(0).is_some_and(is_ident) {
+                s.copy(false);
+            }
+        } else {
+            s.copy(false);
+        }
+    }
+}
+
+fn scan_javascript(s: &mut Scanner) {
+    scan_javascript_code(s, false);
+}
+
+/// Scan code up to the end of the input, or with `in_template` up to the
+/// `}` closing a template literal `${...}` substitution
+fn scan_javascript_code(s: &mut Scanner, in_template: bool) {
+    let mut depth = 0;
+    while let Some(c) = s.peek(0) {
+        if s.starts_with("//") {
+            s.skip_line_comment();
+        } else if s.starts_with("/*") {
+            s.skip_block_comment(false);
+        } else if c == '"' || c == '\'' {
+            let q = c.to_string();
+            s.copy_quoted(&q, &q, true, false);
+        } else if c == '`' {
+            copy_template(s);
+        } else if c == '/' && starts_regex(s) {
+            copy_regex(s);
+        } else if is_ident(c) {
+            while s.peek(0).is_some_and(is_ident) {
+                s.copy(false);
+            }
+        } else if in_template && c == '{' {
+            depth += 1;
+            s.copy(false);
+        } else if in_template && c == '}' {
+            if depth == 0 {
+                return;
+            }
+            depth -= 1;
+            s.copy(false);
+        } else {
+            s.copy(false);
+        }
+    }
+}
+
+/// Copy a template literal, scanning its substitutions as code so nested
+/// templates and comments inside them are handled
+fn copy_template(s: &mut Scanner) {
+    s.copy(false);
+    while let Some(c) = s.peek(0) {
+        if c == '\\' {
+            s.copy_n(2, true);
+        } else if c == '`' {
+            s.copy(true);
+            return;
+        } else if s.starts_with("${") {
+            s.copy_n(2, true);
+            scan_javascript_code(s, true);
+            s.copy(false);
+        } else {
+            s.copy(true);
+        }
+    }
+}
+
+/// Whether a `/` begins a regex literal rather than a division, judged by
+/// the token before it
+fn starts_regex(s: &Scanner) -> bool {
+    match s.last_significant() {
+        None => true,
+        Some(p) if "(,=:[!&|?{};+-*%<>~^".contains(p) => true,
+        Some(_) => {
+            let before = s.out.trim_end();
+            ["return", "typeof", "case", "yield", "await", "in", "of"]
+                .iter()
+                .any(|kw| {
+                    before.ends_with(kw)
+                        && !before[..before.len() - kw.len()]
+                            .chars()
+                            .next_back()
+                            .is_some_and(is_ident)
+                })
+        }
+    }
+}
+
+fn copy_regex(s: &mut Scanner) {
+    s.copy(false);
+    let mut in_class = false;
+    while let Some(c) = s.peek(0) {
+        match c {
+            '\\' => s.copy_n(2, false),
+            '\n' => return,
+            '[' => {
+                in_class = true;
+                s.copy(false);
+            }
+            ']' => {
+                in_class = false;
+                s.copy(false);
+            }
+            '/' if !in_class => {
+                s.copy(false);
+                return;
+            }
+            _ => s.copy(false),
+        }
+    }
+}
+
+fn scan_c_family(s: &mut Scanner) {
+    while let Some(c) = s.peek(0) {
+        if s.starts_with("//") {
+            s.skip_line_comment();
+        } else if s.starts_with("/*") {
+            s.skip_block_comment(false);
+        } else if s.starts_with("\"\"\"") {
+            // Java/Kotlin/Swift/Scala text block
+            s.copy_quoted("\"\"\"", "\"\"\"", true, true);
+        } else if c == '"' || c == '\'' {
+            let q = c.to_string();
+            s.copy_quoted(&q, &q, true, false);
+        } else if c == '`' {
+            // Go raw string
+            s.copy_quoted("`", "`", false, true);
+        } else if c == '@' && s.peek(1) == Some('"') {
+            // C# verbatim string, "" is an escaped quote
+            s.copy_n(2, false);
+            while let Some(c) = s.peek(0) {
+                if s.starts_with("\"\"") {
+                    s.copy_n(2, true);
+                } else if c == '"' {
+                    s.copy(true);
+                    break;
+                } else {
+                    s.copy(true);
+                }
+            }
+        } else if is_ident(c) {
+            let start = s.pos;
+            while s.peek(0).is_some_and(is_ident) {
+                s.copy(false);
+            }
+            // C++ raw string: R"delim(...)delim" with optional u8/u/U/L prefix
+            let prefix: String = s.chars[start..s.pos].iter().collect();
+            if matches!(prefix.as_str(), "R" | "u8R" | "uR" | "UR" | "LR") && s.peek(0) == Some('"') {
+                let delim: String = s.chars[s.pos + 1..]
+                    .iter()
+                    .take(17)
+                    .take_while(|&&c| c != '(')
+                    .collect();
+                if s.peek(1 + delim.chars().count()) == Some('(') {
+                    let open = format!("\"{}(", delim);
+                    let close = format!("){}\"", delim);
+                    s.copy_quoted(&open, &close, false, true);
+                }
+            }
+        } else {
+            s.copy(false);
+        }
+    }
+}
+
+fn scan_python(s: &mut Scanner) {
+    if s.starts_with("#!") {
+        s.copy_line(false);
+    }
+    scan_python_code(s, false);
+}
+
+/// Scan code up to the end of the input, or with `in_fstring` up to the `}`
+/// closing an f-string replacement field
+fn scan_python_code(s: &mut Scanner, in_fstring: bool) {
+    let mut depth: usize = 0;
+    while let Some(c) = s.peek(0) {
+        if c == '#' && !in_fstring {
+            s.skip_line_comment();
+        } else if c == '"' || c == '\'' {
+            copy_python_string(s, c);
+        } else if in_fstring && "([{".contains(c) {
+            depth += 1;
+            s.copy(false);
+        } else if in_fstring && ")]}".contains(c) {
+            if depth == 0 && c == '}' {
+                return;
+            }
+            depth = depth.saturating_sub(1);
+            s.copy(false);
+        } else {
+            s.copy(false);
+        }
+    }
+}
+
+/// Copy a string literal. In f-strings (and t-strings) the replacement
+/// fields are scanned as code, so they may hold strings with the same quotes.
+fn copy_python_string(s: &mut Scanner, quote: char) {
+    let prefix_start = (0..s.pos).rev().take_while(|&i| is_ident(s.chars[i])).last().unwrap_or(s.pos);
+    let prefix: String = s.chars[prefix_start..s.pos].iter().collect::<String>().to_lowercase();
+    let formatted = prefix.len() <= 2
+        && prefix.chars().all(|c| "rfbut".contains(c))
+        && prefix.chars().any(|c| c == 'f' || c == 't');
+
+    let triple: String = std::iter::repeat_n(quote, 3).collect();
+    let multiline = s.starts_with(&triple);
+    let delimiter = if multiline { triple } else { quote.to_string() };
+    if !formatted {
+        s.copy_quoted(&delimiter, &delimiter, true, multiline);
+        return;
+    }
+
+    s.copy_n(delimiter.chars().count(), false);
+    while let Some(c) = s.peek(0) {
+        if c == '\\' {
+            s.copy_n(2, true);
+        } else if s.starts_with(&delimiter) {
+            s.copy_n(delimiter.chars().count(), true);
+            return;
+        } else if c == '\n' && !multiline {
+            return;
+        } else if s.starts_with("{{") {
+            s.copy_n(2, true);
+        } else if c == '{' {
+            s.copy(true);
+            scan_python_code(s, true);
+            s.copy(false);
+        } else {
+            s.copy(true);
+        }
+    }
+}
+
+fn scan_shell(s: &mut Scanner) {
+    if s.starts_with("#!") {
+        s.copy_line(false);
+    }
+    // Heredoc delimiters waiting for the end of the current line
+    let mut heredocs: Vec<(String, bool)> = Vec::new();
+    while let Some(c) = s.peek(0) {
+        match c {
+            '#' if s.prev().is_none_or(|p| p.is_whitespace() || ";&|()<>".contains(p)) => {
+                s.skip_line_comment();
+            }
+            '\\' => s.copy_n(2, false),
+            '\'' => {
+                // $'...' strings honor backslash escapes, plain '...' do not
+                let escapes = s.prev() == Some('$');
+                s.copy_quoted("'", "'", escapes, true);
+            }
+            '"' | '`' => {
+                let q = c.to_string();
+                s.copy_quoted(&q, &q, true, true);
+            }
+            '<' if s.starts_with("<<") && !s.starts_with("<<<") => {
+                s.copy_n(2, false);
+                if let Some(heredoc) = parse_heredoc(s) {
+                    heredocs.push(heredoc);
+                }
+            }
+            '\n' if !heredocs.is_empty() => {
+                s.emit('\n', true);
+                s.pos += 1;
+                for (delimiter, strip_tabs) in heredocs.drain(..) {
+                    copy_heredoc_body(s, &delimiter, strip_tabs);
+                }
+            }
+            _ => s.copy(false),
+        }
+    }
+}
+
+/// Parse the delimiter after `<<`, copying it to the output
+fn parse_heredoc(s: &mut Scanner) -> Option<(String, bool)> {
+    let strip_tabs = s.peek(0) == Some('-');
+    if strip_tabs {
+        s.copy(false);
+    }
+    while s.peek(0).is_some_and(|c| c == ' ' || c == '\t') {
+        s.copy(false);
+    }
+    let quote = s.peek(0).filter(|&c| c == '\'' || c == '"');
+    if quote.is_some() {
+        s.copy(false);
+    }
+    let mut delimiter = String::new();
+    while let Some(c) = s.peek(0).filter(|&c| is_ident(c) || c == '-' || c == '.') {
+        delimiter.push(c);
+        s.copy(false);
+    }
+    if quote.is_some() && s.peek(0) == quote {
+        s.copy(false);
+    }
+    (!delimiter.is_empty()).then_some((delimiter, strip_tabs))
+}
+
+/// Copy heredoc lines verbatim up to and including the delimiter line
+fn copy_heredoc_body(s: &mut Scanner, delimiter: &str, strip_tabs: bool) {
+    while s.peek(0).is_some() {
+        let start = s.pos;
+        s.copy_line(true);
+        let line: String = s.chars[start..s.pos].iter().collect();
+        let line = if strip_tabs { line.trim_start_matches('\t') } else { line.as_str() };
+        let done = line == delimiter;
+        if s.peek(0) == Some('\n') {
+            s.emit('\n', !done);
+            s.pos += 1;
+        }
+        if done {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Comments stripped, with the whitespace left before them trimmed
+    fn strip(source: &str, lang: Language) -> String {
+        strip_comments(source, lang)
+            .text
+            .split('\n')
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn check(cases: &[(&str, &str)], lang: Language) {
+        for (source, expected) in cases {
+            assert_eq!(strip(source, lang), *expected, "{:?} source: {}", lang, source);
+        }
+    }
+
+    #[test]
+    fn javascript_literals() {
+        check(
+            &[
+                ("a(); // note", "a();"),
+                ("'// kept' // dropped", "'// kept'"),
+                ("`x ${`inner // keep`} y` // drop", "`x ${`inner // keep`} y`"),
+                ("`a ${ {b: 1}.b /* gone */ } // c`", "`a ${ {b: 1}.b  } // c`"),
+                ("`${a ? `${b}` : '}'}` // end", "`${a ? `${b}` : '}'}`"),
+                ("`line 1\n// line 2`", "`line 1\n// line 2`"),
+                ("x = /\\/\\/[/*]/g; // re", "x = /\\/\\/[/*]/g;"),
+                ("a = b / c; // div /", "a = b / c;"),
+                ("return /#[//]/.test(s)", "return /#[//]/.test(s)"),
+            ],
+            Language::JavaScript,
+        );
+    }
+
+    #[test]
+    fn rust_literals() {
+        check(
+            &[
+                ("let s = \"// no\"; // yes", "let s = \"// no\";"),
+                ("r\"/* raw */\" /* c */", "r\"/* raw */\""),
+                ("r#\"quote \" // in\"# // out", "r#\"quote \" // in\"#"),
+                ("br##\"a\"#b\"## //", "br##\"a\"#b\"##"),
+                ("let c = '\"'; // q", "let c = '\"';"),
+                ("fn f<'a>(x: &'a str) {} // l", "fn f<'a>(x: &'a str) {}"),
+                ("/* outer /* inner */ still */ x", " x"),
+            ],
+            Language::Rust,
+        );
+    }
+
+    #[test]
+    fn python_literals() {
+        check(
+            &[
+                ("x = 1  # c", "x = 1"),
+                ("s = '# no'  # yes", "s = '# no'"),
+                ("f\"{x!r:#>10}\"  # c", "f\"{x!r:#>10}\""),
+                ("f\"{\"#\"}\"  # c", "f\"{\"#\"}\""),
+                ("f'{d[\"k\"]} {{# literal}}' # c", "f'{d[\"k\"]} {{# literal}}'"),
+                ("rf'{a}\\d#' # c", "rf'{a}\\d#'"),
+                ("\"\"\"doc\n# not a comment\n\"\"\"", "\"\"\"doc\n# not a comment\n\"\"\""),
+                ("f\"\"\"{\n  x}#\"\"\" # c", "f\"\"\"{\n  x}#\"\"\""),
+                ("#!/usr/bin/env python\n# c", "#!/usr/bin/env python\n"),
+            ],
+            Language::Python,
+        );
+    }
+
+    #[test]
+    fn c_family_literals() {
+        check(
+            &[
+                ("auto s = R\"x(// )\" kept)x\"; // c", "auto s = R\"x(// )\" kept)x\";"),
+                ("s := `raw // \\`; // c", "s := `raw // \\`;"),
+                ("var p = @\"C:\\dir\\\" + \"x\"; // c", "var p = @\"C:\\dir\\\" + \"x\";"),
+                ("var q = @\"say \"\"hi\"\" // no\"; // c", "var q = @\"say \"\"hi\"\" // no\";"),
+                ("String t = \"\"\"\n  // text\n  \"\"\"; // c", "String t = \"\"\"\n  // text\n  \"\"\";"),
+                ("char c = '\"'; /* c */", "char c = '\"';"),
+            ],
+            Language::CFamily,
+        );
+    }
+
+    #[test]
+    fn shell_literals() {
+        check(
+            &[
+                ("echo a # c", "echo a"),
+                ("echo a#b", "echo a#b"),
+                ("echo '# no' \"# no\" # yes", "echo '# no' \"# no\""),
+                ("echo ${#arr[@]} # c", "echo ${#arr[@]}"),
+                ("cat <<EOF\n# kept\nEOF\n# dropped", "cat <<EOF\n# kept\nEOF\n"),
+                ("cat <<-'END' # c\n\t# kept\n\tEND", "cat <<-'END'\n\t# kept\n\tEND"),
+                ("echo $'it\\'s' # c", "echo $'it\\'s'"),
+            ],
+            Language::Shell,
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Parser};
+
+use crate::content::{BodyLine, FileBody};
+
+/// How much of a file goes into the output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InclusionMode {
+    /// The whole file content
+    #[default]
+    Full,
+    /// Signatures, types and doc comments with function bodies elided
+    Outline,
+}
+
+/// Grammar and node kinds used to find function bodies in one language
+struct OutlineSpec {
+    language: tree_sitter::Language,
+    /// Node kinds whose `body` field gets elided
+    functions: &'static [&'static str],
+    /// Body node kinds that may be elided (e.g. not expression-bodied arrows)
+    bodies: &'static [&'static str],
+    /// Text written in place of an elided body
+    placeholder: &'static str,
+    /// Keep a leading docstring inside the body (Python)
+    keep_docstring: bool,
+}
+
+const BRACE_PLACEHOLDER: &str = "{ ... }";
+
+const JS_FUNCTIONS: &[&str] = &[
+    "function_declaration",
+    "function_expression",
+    "generator_function_declaration",
+    "generator_function",
+    "arrow_function",
+    "method_definition",
+];
+
+fn spec_for(path: &Path) -> Option<OutlineSpec> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let spec = match ext.as_str() {
+        "rs" => OutlineSpec {
+            language: tree_sitter_rust::LANGUAGE.into(),
+            functions: &["function_item"],
+            bodies: &["block"],
+            placeholder: BRACE_PLACEHOLDER,
+            keep_docstring: false,
+        },
+        "py" | "pyw" | "pyi" => OutlineSpec {
+            language: tree_sitter_python::LANGUAGE.into(),
+            functions: &["function_definition"],
+            bodies: &["block"],
+            placeholder: "...",
+            keep_docstring: true,
+        },
+        "js" | "jsx" | "mjs" | "cjs" => OutlineSpec {
+            language: tree_sitter_javascript::LANGUAGE.into(),
+            functions: JS_FUNCTIONS,
+            bodies: &["statement_block"],
+            placeholder: BRACE_PLACEHOLDER,
+            keep_docstring: false,
+        },
+        "ts" | "mts" | "cts" | "tsx" => OutlineSpec {
+            language: if ext == "tsx" {
+                tree_sitter_typescript::LANGUAGE_TSX.into()
+            } else {
+                tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()
+            },
+            functions: JS_FUNCTIONS,
+            bodies: &["statement_block"],
+            placeholder: BRACE_PLACEHOLDER,
+            keep_docstring: false,
+        },
+        "go" => OutlineSpec {
+            language: tree_sitter_go::LANGUAGE.into(),
+            functions: &["function_declaration", "method_declaration", "func_literal"],
+            bodies: &["block"],
+            placeholder: BRACE_PLACEHOLDER,
+            keep_docstring: false,
+        },
+        "c" | "h" => OutlineSpec {
+            language: tree_sitter_c::LANGUAGE.into(),
+            functions: &["function_definition"],
+            bodies: &["compound_statement"],
+            placeholder: BRACE_PLACEHOLDER,
+            keep_docstring: false,
+        },
+        _ => return None,
+    };
+    Some(spec)
+}
+
+/// Whether `outline` supports the language of `path`
+pub(crate) fn can_outline(path: &Path) -> bool {
+    spec_for(path).is_some()
+}
+
+/// Replace function bodies with a placeholder, keeping signatures, type
+/// definitions, fields and doc comments. Remaining lines keep their source
+/// line numbers. Returns `false` when the language is not supported or the
+/// body was already truncated, leaving it untouched.
+pub fn outline(body: &mut FileBody, path: &Path) -> Result<bool, String> {
+    let Some(spec) = spec_for(path) else {
+        return Ok(false);
+    };
+    if body.lines.iter().any(|l| matches!(l, BodyLine::Omitted { .. })) {
+        return Ok(false);
+    }
+    if body.lines.is_empty() {
+        return Ok(true);
+    }
+
+    let source = body
+        .lines
+        .iter()
+        .filter_map(|l| match l {
+            BodyLine::Text { text, .. } => Some(text.as_str()),
+            BodyLine::Omitted { .. } => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&spec.language)
+        .map_err(|e| format!("Failed to load grammar: {}", e))?;
+    let tree = parser
+        .parse(&source, None)
+        .ok_or_else(|| "Failed to parse file".to_string())?;
+
+    let mut elided = Vec::new();
+    collect_bodies(tree.root_node(), &spec, &mut elided);
+
+    body.lines = elide(&source, &elided, spec.placeholder);
+    Ok(true)
+}
+
+/// Collect the byte ranges of function bodies to elide, outermost first
+fn collect_bodies(node: Node, spec: &OutlineSpec, out: &mut Vec<(usize, usize)>) {
+    if spec.functions.contains(&node.kind()) {
+        if let Some(body) = node
+            .child_by_field_name("body")
+            .filter(|b| spec.bodies.contains(&b.kind()))
+        {
+            if let Some(range) = body_range(body, spec) {
+                out.push(range);
+            }
+            // Nested functions disappear with the body
+            return;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_bodies(child, spec, out);
+    }
+}
+
+/// Byte range of a body to replace, skipping a leading docstring if needed
+fn body_range(body: Node, spec: &OutlineSpec) -> Option<(usize, usize)> {
+    if !spec.keep_docstring {
+        return Some((body.start_byte(), body.end_byte()));
+    }
+
+    let mut cursor = body.walk();
+    let mut statements = body.named_children(&mut cursor);
+    let first = statements.next()?;
+    let is_docstring = first.kind() == "expression_statement"
+        && first.named_child(0).is_some_and(|n| n.kind() == "string");
+    if !is_docstring {
+        return Some((body.start_byte(), body.end_byte()));
+    }
+    // Elide whatever follows the docstring, if anything
+    statements
+        .next()
+        .map(|next| (next.start_byte(), body.end_byte()))
+}
+
+/// Rebuild the numbered lines with the given byte ranges replaced by the placeholder
+fn elide(source: &str, ranges: &[(usize, usize)], placeholder: &str) -> Vec<BodyLine> {
+    let mut builder = LineBuilder { lines: Vec::new(), current: String::new(), number: 1, source_line: 1 };
+    let mut pos = 0;
+    for &(start, end) in ranges {
+        builder.push(&source[pos..start]);
+        builder.current.push_str(placeholder);
+        // Skipped lines still count towards the source numbering
+        builder.source_line += source[start..end].matches('\n').count();
+        pos = end;
+    }
+    builder.push(&source[pos..]);
+    builder.lines.push(BodyLine::Text { number: builder.number, text: builder.current });
+    builder.lines
+}
+
+struct LineBuilder {
+    lines: Vec<BodyLine>,
+    current: String,
+    /// Source line number of the line being built
+    number: usize,
+    /// Source line number at the current read position
+    source_line: usize,
+}
+
+impl LineBuilder {
+    fn push(&mut self, text: &str) {
+        let mut parts = text.split('\n');
+        if let Some(first) = parts.next() {
+            self.current.push_str(first);
+        }
+        for part in parts {
+            let text = std::mem::replace(&mut self.current, part.to_string());
+            self.lines.push(BodyLine::Text { number: self.number, text });
+            self.source_line += 1;
+            self.number = self.source_line;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outlined(source: &str, file_name: &str) -> String {
+        let mut body = FileBody::from_text(source);
+        assert!(outline(&mut body, Path::new(file_name)).unwrap());
+        body.render(true)
+    }
+
+    #[test]
+    fn methods_inside_impl_blocks_are_elided() {
+        let source = "impl Parser {\n    /// Reads a token\n    fn next(&mut self) -> Token {\n        self.pos += 1;\n        self.tokens[self.pos]\n    }\n}\n";
+        assert_eq!(
+            outlined(source, "parser.rs"),
+            "1 | impl Parser {\n2 |     /// Reads a token\n3 |     fn next(&mut self) -> Token { ... }\n7 | }\n"
+        );
+    }
+
+    #[test]
+    fn python_docstring_is_kept() {
+        let source = "def area(r):\n    \"\"\"Area of a circle.\"\"\"\n    import math\n    return math.pi * r * r\n\ndef noop():\n    \"\"\"Does nothing.\"\"\"\n";
+        assert_eq!(
+            outlined(source, "shapes.py"),
+            "1 | def area(r):\n2 |     \"\"\"Area of a circle.\"\"\"\n3 |     ...\n5 | \n6 | def noop():\n7 |     \"\"\"Does nothing.\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn lines_after_elided_bodies_keep_their_numbers() {
+        let source = "fn a() {\n    1;\n    2;\n}\n\nstruct S;\nfn b() { 3 }\nconst C: u8 = 4;\n";
+        let mut body = FileBody::from_text(source);
+        outline(&mut body, Path::new("lib.rs")).unwrap();
+        let numbers: Vec<usize> = body
+            .lines
+            .iter()
+            .filter_map(|l| match l {
+                BodyLine::Text { number, .. } => Some(*number),
+                BodyLine::Omitted { .. } => None,
+            })
+            .collect();
+        assert_eq!(numbers, [1, 5, 6, 7, 8]);
+        assert_eq!(body.render(false), "fn a() { ... }\n\nstruct S;\nfn b() { ... }\nconst C: u8 = 4;\n");
+    }
+
+    #[test]
+    fn truncated_body_is_left_alone() {
+        let mut body = FileBody::from_text("fn a() {\n    1;\n}\n");
+        body.lines.insert(1, BodyLine::Omitted { count: 1 });
+        assert!(!outline(&mut body, Path::new("lib.rs")).unwrap());
+    }
+}
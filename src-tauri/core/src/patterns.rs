@@ -0,0 +1,95 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::scan::{scan_tree, FileNode, ScanOptions};
+
+/// Include/exclude globs matched against paths relative to the scanned root.
+/// `*` stays within one path component, `**` spans directories.
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+pub(crate) fn build_set(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build patterns: {}", e))
+}
+
+impl PathFilter {
+    /// With no include patterns every file is included
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, String> {
+        Ok(Self {
+            include: if include.is_empty() { None } else { Some(build_set(include)?) },
+            exclude: build_set(exclude)?,
+        })
+    }
+
+    pub fn matches(&self, relative: &Path) -> bool {
+        self.include.as_ref().is_none_or(|set| set.is_match(relative))
+            && !self.exclude.is_match(relative)
+    }
+}
+
+/// Selection defined by include/exclude globs relative to a folder, e.g.
+/// `src/**/*.rs` minus `**/tests/**`. It is expanded against the current
+/// scan, so renamed and newly added files are picked up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelectionPatterns {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl SelectionPatterns {
+    pub fn filter(&self) -> Result<PathFilter, String> {
+        PathFilter::new(&self.include, &self.exclude)
+    }
+
+    /// Scan `folder_path` with `options` and return the files matching the
+    /// patterns, in tree order
+    pub fn resolve(&self, folder_path: &str, options: &ScanOptions) -> Result<Vec<String>, String> {
+        let filter = self.filter()?;
+        let root = Path::new(folder_path)
+            .canonicalize()
+            .map_err(|e| format!("Cannot open {}: {}", folder_path, e))?;
+        let tree = scan_tree(&root.to_string_lossy(), options)?;
+        Ok(select_files(&tree, &root, &filter))
+    }
+}
+
+/// Flatten a scanned tree into the paths of the files matching the filter,
+/// in tree order
+pub fn select_files(nodes: &[FileNode], root: &Path, filter: &PathFilter) -> Vec<String> {
+    let mut selected = Vec::new();
+    collect_files(nodes, root, filter, &mut selected);
+    selected
+}
+
+fn collect_files(nodes: &[FileNode], root: &Path, filter: &PathFilter, out: &mut Vec<String>) {
+    for node in nodes {
+        if node.is_dir {
+            if let Some(children) = &node.children {
+                collect_files(children, root, filter, out);
+            }
+            continue;
+        }
+        // A link to a file inside the root would bundle that file twice
+        if node.symlink_target.as_deref().is_some_and(|target| Path::new(target).starts_with(root)) {
+            continue;
+        }
+        let path = Path::new(&node.path);
+        if filter.matches(path.strip_prefix(root).unwrap_or(path)) {
+            out.push(node.path.clone());
+        }
+    }
+}
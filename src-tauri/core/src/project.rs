@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::generate::GenerateOptions;
+use crate::limits::{FileLimits, TruncationMode};
+use crate::outline::InclusionMode;
+use crate::patterns::{build_set, SelectionPatterns};
+use crate::scan::{ScanOptions, SymlinkPolicy};
+use crate::writer::OutputFormat;
+
+/// Name of the project config file, looked up at the scanned root and its parents
+pub const PROJECT_CONFIG_FILE: &str = ".file-extractor.toml";
+
+/// Team-shared settings checked into a repository. Every key is optional and
+/// layers over the user's settings.
+///
+/// ```toml
+/// source_name = "backend"
+///
+/// [scan]
+/// ignore = ["fixtures", "docs/generated/**"]
+/// max_depth = 8
+/// symlinks = "skip"
+/// hidden_allowlist = [".husky"]
+///
+/// [selection]
+/// include = ["src/**/*.rs"]
+/// exclude = ["**/tests/**"]
+///
+/// [output]
+/// format = "text"
+/// line_numbers = true
+/// mode = "outline"
+///
+/// [limits]
+/// max_tokens = 20000
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub source_name: Option<String>,
+    pub scan: ProjectScan,
+    /// Default selection when the user has none saved for the folder
+    pub selection: Option<SelectionPatterns>,
+    pub output: ProjectOutput,
+    pub limits: ProjectLimits,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectScan {
+    pub ignore: Vec<String>,
+    pub max_depth: Option<usize>,
+    pub symlinks: Option<SymlinkPolicy>,
+    pub include_hidden: Option<bool>,
+    /// Added to the user's allowlist of hidden entries
+    pub hidden_allowlist: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectOutput {
+    pub format: Option<OutputFormat>,
+    pub line_numbers: Option<bool>,
+    pub minify: Option<bool>,
+    pub mode: Option<InclusionMode>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectLimits {
+    pub max_bytes: Option<u64>,
+    pub max_lines: Option<usize>,
+    pub max_tokens: Option<usize>,
+    pub truncation: Option<TruncationMode>,
+}
+
+impl ProjectConfig {
+    /// Parse a config file, pointing at the offending key on error
+    pub fn parse(content: &str, path: &Path) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| describe_error(&e, content, path))?;
+        config.validate().map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        build_set(&self.scan.ignore).map_err(|e| format!("`scan.ignore`: {}", e))?;
+        build_set(&self.scan.hidden_allowlist).map_err(|e| format!("`scan.hidden_allowlist`: {}", e))?;
+        if let Some(selection) = &self.selection {
+            build_set(&selection.include).map_err(|e| format!("`selection.include`: {}", e))?;
+            build_set(&selection.exclude).map_err(|e| format!("`selection.exclude`: {}", e))?;
+        }
+        if self.scan.max_depth == Some(0) {
+            return Err("`scan.max_depth`: must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Scan options with the project's ignore rules, depth, symlink policy and
+    /// hidden entries
+    pub fn scan_options(&self, mut options: ScanOptions) -> ScanOptions {
+        options.ignore.extend(self.scan.ignore.iter().cloned());
+        if let Some(max_depth) = self.scan.max_depth {
+            options.max_depth = max_depth;
+        }
+        if let Some(symlinks) = self.scan.symlinks {
+            options.symlinks = symlinks;
+        }
+        if let Some(include_hidden) = self.scan.include_hidden {
+            options.include_hidden = include_hidden;
+        }
+        options.hidden_allowlist.extend(self.scan.hidden_allowlist.iter().cloned());
+        options
+    }
+
+    /// User limits with the project's limits layered on top
+    pub fn limits(&self, mut limits: FileLimits) -> FileLimits {
+        if self.limits.max_bytes.is_some() {
+            limits.max_bytes = self.limits.max_bytes;
+        }
+        if self.limits.max_lines.is_some() {
+            limits.max_lines = self.limits.max_lines;
+        }
+        if self.limits.max_tokens.is_some() {
+            limits.max_tokens = self.limits.max_tokens;
+        }
+        if let Some(truncation) = &self.limits.truncation {
+            limits.truncation = truncation.clone();
+        }
+        limits
+    }
+
+    /// Default generation options for the project
+    pub fn generate_options(&self, mut options: GenerateOptions) -> GenerateOptions {
+        if let Some(line_numbers) = self.output.line_numbers {
+            options.line_numbers = line_numbers;
+        }
+        if let Some(minify) = self.output.minify {
+            options.minify = minify;
+        }
+        if let Some(mode) = self.output.mode {
+            options.mode = mode;
+        }
+        options
+    }
+}
+
+/// Find and load the project config in `start` or its closest parent that has one
+pub fn discover_project_config(start: &Path) -> Result<Option<(PathBuf, ProjectConfig)>, String> {
+    for dir in start.ancestors() {
+        let path = dir.join(PROJECT_CONFIG_FILE);
+        if !path.is_file() {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let config = ProjectConfig::parse(&content, &path)?;
+        return Ok(Some((path, config)));
+    }
+    Ok(None)
+}
+
+/// Deepest directory containing every path, used to discover the config of a selection
+pub fn common_ancestor(paths: &[String]) -> Option<PathBuf> {
+    let mut paths = paths.iter().map(Path::new);
+    let mut common = paths.next()?.parent()?.to_path_buf();
+    for path in paths {
+        while !path.starts_with(&common) {
+            common = common.parent()?.to_path_buf();
+        }
+    }
+    Some(common)
+}
+
+/// `path:line: `key`: message`, with the key path recovered from the error span
+fn describe_error(error: &toml::de::Error, content: &str, path: &Path) -> String {
+    let message = error.message().trim_end();
+    let Some(span) = error.span() else {
+        return format!("Invalid {}: {}", path.display(), message);
+    };
+    let line = content[..span.start.min(content.len())].matches('\n').count() + 1;
+    match key_at(content, span.start) {
+        Some(key) => format!("Invalid {}:{}: `{}`: {}", path.display(), line, key, message),
+        None => format!("Invalid {}:{}: {}", path.display(), line, message),
+    }
+}
+
+/// Dotted key of the `key = value` line (or `[table]` header) at `offset`
+fn key_at(content: &str, offset: usize) -> Option<String> {
+    let offset = offset.min(content.len());
+    let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = content[line_start..].lines().next().unwrap_or("").trim();
+
+    let table_of = |header: &str| header.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+    if line.starts_with('[') {
+        return Some(table_of(line));
+    }
+    let key = line.split_once('=')?.0.trim().trim_matches('"');
+    let table = content[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|l| l.starts_with('['))
+        .map(table_of);
+    Some(match table {
+        Some(table) => format!("{}.{}", table, key),
+        None => key.to_string(),
+    })
+}
@@ -0,0 +1,551 @@
+use globset::GlobSet;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::fs;
+use std::sync::OnceLock;
+use crate::patterns::build_set;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileNode {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<FileNode>>,
+    /// Whether a directory has visible entries; only set by `list_directory`,
+    /// which leaves `children` empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_children: Option<bool>,
+    /// Directory with entries that were not scanned because of the depth
+    /// limit; list or scan it directly to see them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Resolved target when the entry is a symbolic link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
+}
+
+/// List of directory names to ignore (like .gitignore)
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    "venv",
+    ".venv",
+    "env",
+    ".env",
+    "__pycache__",
+    ".pytest_cache",
+    "target",
+    "dist",
+    "build",
+    ".next",
+    ".nuxt",
+    ".cache",
+    ".idea",
+    ".vscode",
+    ".git",
+    ".svn",
+    ".hg",
+    ".DS_Store",
+    "vendor",
+    ".bundle",
+    ".sass-cache",
+    "coverage",
+    ".nyc_output",
+    ".gradle",
+    ".mvn",
+    "bin",
+    "obj",
+    ".vs",
+];
+
+fn should_ignore_dir(dir_name: &str) -> bool {
+    IGNORED_DIRS.contains(&dir_name.to_lowercase().as_str())
+}
+
+/// Validate that the path is safe and exists
+pub(crate) fn validate_directory_path(dir_path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(dir_path);
+    
+    // Check if path exists
+    if !path.exists() {
+        return Err(format!("Directory does not exist: {}", dir_path));
+    }
+    
+    // Check if path is a directory
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir_path));
+    }
+    
+    // Canonicalize path to resolve any symlinks and normalize
+    let canonical_path = path.canonicalize()
+        .map_err(|e| format!("Failed to canonicalize path: {}", e))?;
+    
+    Ok(canonical_path)
+}
+
+/// Dotfiles and dot-directories that are useful context, listed even when
+/// hidden entries are not
+pub const DEFAULT_HIDDEN_ALLOWLIST: &[&str] = &[
+    ".github",
+    ".gitlab-ci.yml",
+    ".gitignore",
+    ".gitattributes",
+    ".dockerignore",
+    ".editorconfig",
+    ".eslintrc*",
+    ".prettierrc*",
+    ".cargo",
+    ".env.example",
+];
+
+pub(crate) fn default_hidden_allowlist() -> Vec<String> {
+    DEFAULT_HIDDEN_ALLOWLIST.iter().map(|s| s.to_string()).collect()
+}
+
+/// Depth scanned when neither the call, the project nor the settings set one
+pub const DEFAULT_MAX_DEPTH: usize = 6;
+
+/// Options controlling a directory scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Maximum directory depth to descend into
+    pub max_depth: usize,
+    /// Extra globs to skip, matched against the entry name and its path
+    /// relative to the scanned root
+    pub ignore: Vec<String>,
+    /// Directories read in parallel; defaults to the number of CPUs. Capped
+    /// at `MAX_SCAN_THREADS` either way.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    pub symlinks: SymlinkPolicy,
+    /// List entries whose name starts with a dot
+    pub include_hidden: bool,
+    /// Globs of hidden entries listed even when `include_hidden` is off,
+    /// matched like `ignore`
+    pub hidden_allowlist: Vec<String>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            ignore: Vec::new(),
+            threads: None,
+            symlinks: SymlinkPolicy::default(),
+            include_hidden: false,
+            hidden_allowlist: default_hidden_allowlist(),
+        }
+    }
+}
+
+/// Which symbolic links a scan lists. Listed links are marked with their
+/// target; links to directories inside the root are not expanded, as their
+/// contents already appear under the real path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SymlinkPolicy {
+    /// Leave symlinks out
+    Skip,
+    /// List symlinks whose target is inside the scanned root
+    #[default]
+    FollowWithinRoot,
+    /// Also list and expand symlinks leading outside the root, stopping at loops
+    FollowAll,
+}
+
+/// Upper bound of the scan pools; more threads mostly queue up on the disk
+const MAX_SCAN_THREADS: usize = 8;
+
+/// Run `scan` on the shared scan pool, or on a dedicated one when a thread
+/// count is given. Thread counts come from API callers too, so they are
+/// clamped to `1..=MAX_SCAN_THREADS`.
+fn in_scan_pool<R: Send>(threads: Option<usize>, scan: impl FnOnce() -> R + Send) -> Result<R, String> {
+    static POOL: OnceLock<Option<ThreadPool>> = OnceLock::new();
+    
+    let build = |threads: usize| {
+        ThreadPoolBuilder::new()
+            .num_threads(threads.clamp(1, MAX_SCAN_THREADS))
+            .thread_name(|i| format!("scan-{}", i))
+            .build()
+            .map_err(|e| format!("Failed to start scan threads: {}", e))
+    };
+    match threads {
+        Some(threads) => Ok(build(threads)?.install(scan)),
+        None => {
+            let pool = POOL.get_or_init(|| {
+                let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+                build(threads).ok()
+            });
+            // Scan on the calling thread if the pool could not be started
+            Ok(match pool {
+                Some(pool) => pool.install(scan),
+                None => scan(),
+            })
+        }
+    }
+}
+
+/// Scan a directory tree, skipping hidden entries and ignored directories
+pub fn scan_tree(dir_path: &str, options: &ScanOptions) -> Result<Vec<FileNode>, String> {
+    let base_path = validate_directory_path(dir_path)?;
+    let ctx = ScanContext::new(&base_path, options)?;
+    let ancestors = ctx.ancestor_ids(&base_path);
+    
+    // Recursively scan directory, subdirectories in parallel
+    in_scan_pool(options.threads, || {
+        scan_directory_recursive(&ctx, &base_path, &ancestors, 0, options.max_depth)
+    })
+}
+
+/// Directory listed during a streaming scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirListing {
+    /// Path of the listed directory; the scanned root for top-level entries
+    pub parent: String,
+    /// Entries without their children, which arrive in later listings
+    pub nodes: Vec<FileNode>,
+}
+
+/// Number of nodes collected before a batch is handed out
+const STREAM_BATCH_SIZE: usize = 500;
+
+/// One level of `dir_path`, which must be inside `root`. Directories come
+/// without children but with `has_children` set, to be listed on expand;
+/// those at the depth limit are also marked `truncated`.
+pub fn list_directory(root: &str, dir_path: &str, options: &ScanOptions) -> Result<Vec<FileNode>, String> {
+    let base_path = validate_directory_path(root)?;
+    let ctx = ScanContext::new(&base_path, options)?;
+    
+    // Paths below followed symlinks only stay inside the root lexically
+    let outside = || format!("{} is outside {}", dir_path, root);
+    let dir = match Path::new(dir_path).strip_prefix(&base_path) {
+        Ok(relative) if options.symlinks == SymlinkPolicy::FollowAll => {
+            if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+                return Err(outside());
+            }
+            base_path.join(relative)
+        }
+        _ => {
+            let dir = validate_directory_path(dir_path)?;
+            if !dir.starts_with(&base_path) {
+                return Err(outside());
+            }
+            dir
+        }
+    };
+    if !dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir_path));
+    }
+    
+    let depth = dir.strip_prefix(&base_path).map_or(0, |relative| relative.components().count());
+    if depth >= options.max_depth {
+        return Err(format!("{} is beyond the maximum depth of {}", dir_path, options.max_depth));
+    }
+    
+    let ancestors = ctx.ancestor_ids(&dir);
+    let mut nodes = read_level(&ctx, &dir);
+    let at_limit = depth + 1 >= options.max_depth;
+    for node in nodes.iter_mut().filter(|n| n.is_dir) {
+        let has_children = ctx.should_descend(node, &ancestors)
+            && has_visible_entries(&ctx, Path::new(&node.path));
+        node.has_children = Some(has_children);
+        node.truncated = at_limit && has_children;
+    }
+    Ok(nodes)
+}
+
+/// Walk the tree like `scan_tree`, handing out listings in batches as soon
+/// as they are read. Parents are always listed before their children.
+/// Stops early when `on_batch` returns `false`.
+pub fn scan_tree_streaming(
+    dir_path: &str,
+    options: &ScanOptions,
+    mut on_batch: impl FnMut(Vec<DirListing>) -> bool,
+) -> Result<(), String> {
+    let base_path = validate_directory_path(dir_path)?;
+    let ctx = ScanContext::new(&base_path, options)?;
+    
+    if options.max_depth == 0 {
+        return Ok(());
+    }
+    
+    let mut batch = Vec::new();
+    let mut batch_nodes = 0;
+    // Breadth-first, so the top of the tree shows up first
+    let root = (base_path.clone(), ctx.ancestor_ids(&base_path), 0);
+    let mut queue = std::collections::VecDeque::from([root]);
+    while let Some((dir, ancestors, depth)) = queue.pop_front() {
+        let mut nodes = read_level(&ctx, &dir);
+        for node in nodes.iter_mut().filter(|n| n.is_dir) {
+            if !ctx.should_descend(node, &ancestors) {
+                continue;
+            }
+            let path = PathBuf::from(&node.path);
+            if depth + 1 < options.max_depth {
+                let ancestors = ctx.with_ancestor(&ancestors, &path);
+                queue.push_back((path, ancestors, depth + 1));
+            } else {
+                node.truncated = has_visible_entries(&ctx, &path);
+            }
+        }
+        
+        batch_nodes += nodes.len();
+        batch.push(DirListing { parent: dir.to_string_lossy().to_string(), nodes });
+        if batch_nodes >= STREAM_BATCH_SIZE {
+            batch_nodes = 0;
+            if !on_batch(std::mem::take(&mut batch)) {
+                return Ok(());
+            }
+        }
+    }
+    
+    if !batch.is_empty() {
+        on_batch(batch);
+    }
+    Ok(())
+}
+
+/// Identity of a directory, to notice a symlink leading back to one of its
+/// ancestors
+#[cfg(unix)]
+type FileId = (u64, u64);
+#[cfg(not(unix))]
+type FileId = PathBuf;
+
+#[cfg(unix)]
+fn file_id(path: &Path) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(path: &Path) -> Option<FileId> {
+    path.canonicalize().ok()
+}
+
+/// What every level of a scan needs to know
+pub(crate) struct ScanContext<'a> {
+    /// Canonical root of the scan
+    base_path: &'a Path,
+    ignore: GlobSet,
+    symlinks: SymlinkPolicy,
+    include_hidden: bool,
+    hidden_allowlist: GlobSet,
+}
+
+impl<'a> ScanContext<'a> {
+    pub(crate) fn new(base_path: &'a Path, options: &ScanOptions) -> Result<Self, String> {
+        Ok(Self {
+            base_path,
+            ignore: build_set(&options.ignore)?,
+            symlinks: options.symlinks,
+            include_hidden: options.include_hidden,
+            hidden_allowlist: build_set(&options.hidden_allowlist)?,
+        })
+    }
+    
+    /// Whether an entry is left out for being hidden; independent of
+    /// `IGNORED_DIRS`, which applies either way
+    fn is_hidden(&self, name: &str, relative: &Path) -> bool {
+        name.starts_with('.')
+            && !self.include_hidden
+            && !self.hidden_allowlist.is_match(name)
+            && !self.hidden_allowlist.is_match(relative)
+    }
+    
+    /// Whether a scan would list `path`, judging by its name and those of
+    /// the directories between it and the root
+    #[cfg(feature = "watch")]
+    pub(crate) fn is_listed(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(self.base_path) else {
+            return false;
+        };
+        let mut current = self.base_path.to_path_buf();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            let Component::Normal(name) = component else {
+                return false;
+            };
+            current.push(name);
+            let name = name.to_string_lossy();
+            let relative = current.strip_prefix(self.base_path).unwrap_or(&current);
+            let is_dir = components.peek().is_some() || current.is_dir();
+            if self.is_hidden(&name, relative)
+                || (is_dir && should_ignore_dir(&name))
+                || self.ignore.is_match(name.as_ref())
+                || self.ignore.is_match(relative)
+            {
+                return false;
+            }
+        }
+        true
+    }
+    
+    /// Whether to list the children of a directory node. Symlinks resolving
+    /// inside the root are not expanded, since their contents are already
+    /// listed under the real path, nor are those leading back to an ancestor.
+    fn should_descend(&self, node: &FileNode, ancestors: &[FileId]) -> bool {
+        if !node.is_dir {
+            return false;
+        }
+        match &node.symlink_target {
+            None => true,
+            Some(target) if Path::new(target).starts_with(self.base_path) => false,
+            Some(target) => file_id(Path::new(target)).is_some_and(|id| !ancestors.contains(&id)),
+        }
+    }
+    
+    /// Identities of `dir` and the directories between it and the root.
+    /// Only symlinks to outside the root can loop, so this is skipped
+    /// unless they are followed.
+    fn ancestor_ids(&self, dir: &Path) -> Vec<FileId> {
+        if self.symlinks != SymlinkPolicy::FollowAll {
+            return Vec::new();
+        }
+        dir.ancestors()
+            .take_while(|path| path.starts_with(self.base_path))
+            .filter_map(file_id)
+            .collect()
+    }
+    
+    fn with_ancestor(&self, ancestors: &[FileId], dir: &Path) -> Vec<FileId> {
+        let mut ancestors = ancestors.to_vec();
+        if self.symlinks == SymlinkPolicy::FollowAll {
+            ancestors.extend(file_id(dir));
+        }
+        ancestors
+    }
+}
+
+fn scan_directory_recursive(
+    ctx: &ScanContext,
+    dir_path: &Path,
+    ancestors: &[FileId],
+    current_depth: usize,
+    max_depth: usize,
+) -> Vec<FileNode> {
+    if current_depth >= max_depth {
+        return Vec::new();
+    }
+    
+    // Children are filled in place, so the order of `read_level` is kept
+    let mut nodes = read_level(ctx, dir_path);
+    nodes.par_iter_mut().filter(|node| ctx.should_descend(node, ancestors)).for_each(|node| {
+        let path = Path::new(&node.path);
+        if current_depth + 1 >= max_depth {
+            // Say so rather than showing the directory as empty
+            node.truncated = has_visible_entries(ctx, path);
+            return;
+        }
+        let ancestors = ctx.with_ancestor(ancestors, path);
+        let children = scan_directory_recursive(ctx, path, &ancestors, current_depth + 1, max_depth);
+        node.children = if children.is_empty() { None } else { Some(children) };
+    });
+    nodes
+}
+
+/// Visible entries of one directory without their children, directories
+/// first, then files, both alphabetically
+fn read_level(ctx: &ScanContext, dir_path: &Path) -> Vec<FileNode> {
+    // Ensure we don't escape from the base directory. Paths are built from
+    // the canonical root and entry names, and symlinks are only descended
+    // into as `should_descend` allows, so a prefix check is enough;
+    // canonicalizing every directory is slow on network drives.
+    if !dir_path.starts_with(ctx.base_path) {
+        return Vec::new();
+    }
+    
+    let entries = match fs::read_dir(dir_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            // Log permission errors for debugging but continue gracefully
+            eprintln!("Warning: Cannot read directory {:?}: {}", dir_path, e);
+            return Vec::new();
+        }
+    };
+    
+    let mut nodes: Vec<FileNode> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| visible_entry(ctx, &entry))
+        .collect();
+    
+    // Sort: directories first, then files, both alphabetically
+    nodes.sort_by(|a, b| {
+        match (a.is_dir, b.is_dir) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            // Fall back to the exact name so the order never depends on the file system
+            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.name.cmp(&b.name)),
+        }
+    });
+    nodes
+}
+
+/// Whether a directory has anything `read_level` would list
+fn has_visible_entries(ctx: &ScanContext, dir_path: &Path) -> bool {
+    fs::read_dir(dir_path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .any(|entry| visible_entry(ctx, &entry).is_some())
+        })
+        .unwrap_or(false)
+}
+
+/// Node for a directory entry, or `None` when it is hidden, ignored or a
+/// symlink the policy leaves out
+fn visible_entry(ctx: &ScanContext, entry: &fs::DirEntry) -> Option<FileNode> {
+    let entry_path = entry.path();
+    let relative = entry_path.strip_prefix(ctx.base_path).unwrap_or(&entry_path);
+    
+    let name = entry_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| entry_path.to_string_lossy().to_string());
+    
+    // Skip hidden files/directories unless enabled or allowlisted
+    if ctx.is_hidden(&name, relative) {
+        return None;
+    }
+    
+    // Read from the directory listing on most platforms, without a stat call
+    let file_type = entry.file_type().ok()?;
+    let (is_dir, symlink_target) = if file_type.is_symlink() {
+        if ctx.symlinks == SymlinkPolicy::Skip {
+            return None;
+        }
+        // Broken links have nothing to include
+        let target = entry_path.canonicalize().ok()?;
+        if ctx.symlinks == SymlinkPolicy::FollowWithinRoot && !target.starts_with(ctx.base_path) {
+            return None;
+        }
+        (target.is_dir(), Some(target.to_string_lossy().to_string()))
+    } else {
+        (file_type.is_dir(), None)
+    };
+    
+    // Skip ignored directories (like node_modules, venv, etc.)
+    if is_dir && should_ignore_dir(&name) {
+        return None;
+    }
+    
+    // Skip entries matching the configured ignore globs
+    if !ctx.ignore.is_empty() && (ctx.ignore.is_match(&name) || ctx.ignore.is_match(relative)) {
+        return None;
+    }
+    
+    Some(FileNode {
+        name,
+        path: entry_path.to_string_lossy().to_string(),
+        is_dir,
+        children: None,
+        has_children: None,
+        truncated: false,
+        symlink_target,
+    })
+}
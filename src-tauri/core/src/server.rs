@@ -0,0 +1,359 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use crate::generate::{estimate_files, generate, FileReport, GenerateOptions};
+use crate::scan::{scan_tree, ScanOptions};
+use crate::settings::{resolve_generate_options, resolve_scan_options};
+use crate::writer::TextWriter;
+
+const MAX_HEADER_LINES: usize = 64;
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024; // 8MB
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Localhost-only HTTP/JSON API exposing scan, token estimate and generate.
+/// Every endpoint except `GET /health` requires `Authorization: Bearer <token>`.
+pub struct ApiServer {
+    listener: TcpListener,
+    token: Arc<String>,
+    shutdown: Arc<AtomicBool>,
+}
+
+/// Stops a running `ApiServer` from another thread
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the blocking accept loop
+        let _ = TcpStream::connect(self.addr);
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+}
+
+impl ApiServer {
+    /// Bind to 127.0.0.1 on `port` (0 picks a free port)
+    pub fn bind(port: u16, token: &str) -> Result<Self, String> {
+        if token.is_empty() {
+            return Err("API server token must not be empty".to_string());
+        }
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .map_err(|e| format!("Failed to bind API server to port {}: {}", port, e))?;
+        Ok(Self {
+            listener,
+            token: Arc::new(token.to_string()),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, String> {
+        self.listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read API server address: {}", e))
+    }
+
+    pub fn shutdown_handle(&self) -> Result<ShutdownHandle, String> {
+        Ok(ShutdownHandle {
+            addr: self.local_addr()?,
+            shutdown: Arc::clone(&self.shutdown),
+        })
+    }
+
+    /// Serve requests until shut down, one thread per connection
+    pub fn run(self) -> Result<(), String> {
+        for stream in self.listener.incoming() {
+            if self.shutdown.load(Ordering::SeqCst) {
+                break;
+            }
+            let Ok(stream) = stream else { continue };
+            let token = Arc::clone(&self.token);
+            thread::spawn(move || handle_connection(stream, &token));
+        }
+        Ok(())
+    }
+}
+
+/// Random 256-bit token, hex encoded
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    host: Option<String>,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(e) => Self::error(500, &format!("Failed to serialize response: {}", e)),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ScanRequest {
+    dir_path: String,
+    /// The settings and project defaults apply when omitted
+    options: Option<ScanOptions>,
+}
+
+#[derive(Deserialize)]
+struct GenerateRequest {
+    selected_paths: Vec<String>,
+    #[serde(default)]
+    source_name: String,
+    /// Project defaults and the settings limits apply when omitted
+    options: Option<GenerateOptions>,
+}
+
+#[derive(Serialize)]
+struct GenerateResponse {
+    output: String,
+    files: Vec<FileReport>,
+}
+
+fn handle_connection(mut stream: TcpStream, token: &str) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let response = match read_request(&stream) {
+        Ok(request) => route(&request, token),
+        Err(response) => response,
+    };
+    let _ = write_response(&mut stream, &response);
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let bad_request = |message: &str| Response::error(400, message);
+
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|_| bad_request("Failed to read request"))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("Malformed request line"));
+    };
+    let method = method.to_string();
+    // Ignore any query string
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut host = None;
+    let mut authorization = None;
+    let mut content_length = 0usize;
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        reader
+            .read_line(&mut line)
+            .map_err(|_| bad_request("Failed to read headers"))?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            let mut body = vec![0; content_length];
+            reader
+                .read_exact(&mut body)
+                .map_err(|_| bad_request("Failed to read request body"))?;
+            return Ok(Request { method, path, host, authorization, body });
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad_request("Malformed header"));
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "host" => host = Some(value.to_string()),
+            "authorization" => authorization = Some(value.to_string()),
+            "content-length" => {
+                content_length = value
+                    .parse()
+                    .map_err(|_| bad_request("Invalid Content-Length"))?;
+                if content_length > MAX_BODY_BYTES {
+                    return Err(Response::error(413, "Request body too large"));
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(bad_request("Too many headers"))
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+fn route(request: &Request, token: &str) -> Response {
+    // Reject requests addressed to another host name (DNS rebinding from a browser)
+    let host_is_local = request.host.as_deref().is_some_and(|host| {
+        let name = host.rsplit_once(':').map_or(host, |(name, _)| name);
+        matches!(name, "127.0.0.1" | "localhost" | "[::1]")
+    });
+    if !host_is_local {
+        return Response::error(403, "Only localhost requests are accepted");
+    }
+
+    if request.path == "/health" {
+        return match request.method.as_str() {
+            "GET" => Response::json(&serde_json::json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") })),
+            _ => Response::error(405, "Method not allowed"),
+        };
+    }
+
+    let authorized = request
+        .authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| constant_time_eq(given.trim().as_bytes(), token.as_bytes()));
+    if !authorized {
+        return Response::error(401, "Missing or invalid bearer token");
+    }
+
+    let handler: fn(&[u8]) -> Result<Response, String> = match request.path.as_str() {
+        "/scan" => handle_scan,
+        "/estimate" => handle_estimate,
+        "/generate" => handle_generate,
+        _ => return Response::error(404, "Unknown endpoint"),
+    };
+    if request.method != "POST" {
+        return Response::error(405, "Method not allowed");
+    }
+    handler(&request.body).unwrap_or_else(|e| Response::error(400, &e))
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T, String> {
+    serde_json::from_slice(body).map_err(|e| format!("Invalid request body: {}", e))
+}
+
+fn handle_scan(body: &[u8]) -> Result<Response, String> {
+    let request: ScanRequest = parse_body(body)?;
+    let options = match request.options {
+        Some(options) => options,
+        None => resolve_scan_options(&request.dir_path)?,
+    };
+    Ok(Response::json(&scan_tree(&request.dir_path, &options)?))
+}
+
+fn handle_estimate(body: &[u8]) -> Result<Response, String> {
+    let request: GenerateRequest = parse_body(body)?;
+    let options = resolve_generate_options(&request.selected_paths, request.options)?;
+    Ok(Response::json(&estimate_files(&request.selected_paths, &options)?))
+}
+
+fn handle_generate(body: &[u8]) -> Result<Response, String> {
+    let request: GenerateRequest = parse_body(body)?;
+    let options = resolve_generate_options(&request.selected_paths, request.options)?;
+    let mut writer = TextWriter::new(Vec::new());
+    let files = generate(&mut writer, &request.selected_paths, &request.source_name, &options)?;
+    let output = String::from_utf8_lossy(&writer.into_inner()).into_owned();
+    Ok(Response::json(&GenerateResponse { output, files }))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "test-token";
+
+    /// Send a raw request and return the status code and body
+    fn send(addr: SocketAddr, method: &str, path: &str, headers: &[&str], body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut request = format!("{} {} HTTP/1.1\r\nContent-Length: {}\r\n", method, path, body.len());
+        for header in headers {
+            request.push_str(header);
+            request.push_str("\r\n");
+        }
+        request.push_str("\r\n");
+        request.push_str(body);
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = response.split_once("\r\n\r\n").map(|(_, b)| b.to_string()).unwrap_or_default();
+        (status, body)
+    }
+
+    #[test]
+    fn serves_health_and_checks_host_and_token() {
+        let server = ApiServer::bind(0, TOKEN).unwrap();
+        let addr = server.local_addr().unwrap();
+        let shutdown = server.shutdown_handle().unwrap();
+        let running = thread::spawn(move || server.run());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        let scan = serde_json::json!({ "dir_path": dir.path() }).to_string();
+        let host = format!("Host: 127.0.0.1:{}", addr.port());
+        let bearer = format!("Authorization: Bearer {}", TOKEN);
+
+        let (status, body) = send(addr, "GET", "/health", &[&host], "");
+        assert_eq!(status, 200);
+        assert!(body.contains("\"status\":\"ok\""));
+
+        let (status, _) = send(addr, "POST", "/scan", &[&host], &scan);
+        assert_eq!(status, 401);
+        let (status, _) = send(addr, "POST", "/scan", &[&host, "Authorization: Bearer wrong"], &scan);
+        assert_eq!(status, 401);
+
+        let (status, _) = send(addr, "GET", "/health", &["Host: attacker.example:7878"], "");
+        assert_eq!(status, 403);
+        let (status, _) = send(addr, "POST", "/scan", &["Host: attacker.example", &bearer], &scan);
+        assert_eq!(status, 403);
+
+        let (status, body) = send(addr, "POST", "/scan", &[&host, &bearer], &scan);
+        assert_eq!(status, 200);
+        let tree: Vec<crate::scan::FileNode> = serde_json::from_str(&body).unwrap();
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].name, "main.rs");
+
+        shutdown.shutdown();
+        running.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,521 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::limits::FileLimits;
+use crate::store::{deserialize_lenient, Schema};
+use crate::patterns::SelectionPatterns;
+use crate::generate::GenerateOptions;
+use crate::project::{common_ancestor, discover_project_config};
+use crate::scan::{default_hidden_allowlist, ScanOptions, SymlinkPolicy, DEFAULT_MAX_DEPTH};
+use crate::storage::{config_dir, data_dir};
+
+/// Port of the API server when none is configured
+pub const DEFAULT_SERVER_PORT: u16 = 7878;
+
+/// `settings.json`. Missing or invalid fields fall back to their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsData {
+    pub dark_mode: bool,
+    pub recent_sources: Vec<String>,
+    pub last_folder: String,
+    pub file_limits: FileLimits,
+    pub api_server: ApiServerSettings,
+    /// Directory levels scanned when a call gives none
+    pub max_scan_depth: usize,
+    pub scan_symlinks: SymlinkPolicy,
+    /// List dotfiles and dot-directories when scanning
+    pub show_hidden: bool,
+    /// Hidden entries listed even when `show_hidden` is off
+    pub hidden_allowlist: Vec<String>,
+}
+
+/// v1: unversioned. v2: adds the `version` key.
+const SETTINGS_SCHEMA: Schema = Schema {
+    file_name: "settings.json",
+    version: 2,
+    migrations: &[|settings| Ok(settings)],
+};
+
+/// v1: unversioned map of folder to selection; entries written before
+/// presets existed hold only a file list.
+/// v2: the map moves under `folders`, next to the `version` key, and every
+/// entry has presets.
+const SELECTIONS_SCHEMA: Schema = Schema {
+    file_name: "folder_selections.json",
+    version: 2,
+    migrations: &[migrate_selections_v1],
+};
+
+/// Move the folders under `folders`, keeping a single-list entry's files
+/// both as the last selection and as a preset
+fn migrate_selections_v1(mut folders: serde_json::Value) -> Result<serde_json::Value, String> {
+    if let Some(folders) = folders.as_object_mut() {
+        for entry in folders.values_mut() {
+            let Some(entry) = entry.as_object_mut() else { continue };
+            if entry.contains_key("presets") {
+                continue;
+            }
+            let files = entry.get("files").cloned().unwrap_or_else(|| json!([]));
+            let presets = match files.as_array() {
+                Some(list) if !list.is_empty() => {
+                    let now = now_secs();
+                    json!([{
+                        "name": LEGACY_PRESET_NAME,
+                        "files": files,
+                        "created_at": now,
+                        "updated_at": now,
+                    }])
+                }
+                _ => json!([]),
+            };
+            entry.insert("presets".to_string(), presets);
+        }
+    }
+    Ok(json!({ "folders": folders }))
+}
+
+/// Localhost HTTP/JSON API, off by default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiServerSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Bearer token; generated on first start when empty
+    pub token: String,
+}
+
+impl Default for ApiServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_SERVER_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+impl Default for SettingsData {
+    fn default() -> Self {
+        Self {
+            dark_mode: true,
+            recent_sources: Vec::new(),
+            last_folder: String::new(),
+            file_limits: FileLimits::default(),
+            api_server: ApiServerSettings::default(),
+            max_scan_depth: DEFAULT_MAX_DEPTH,
+            scan_symlinks: SymlinkPolicy::default(),
+            show_hidden: false,
+            hidden_allowlist: default_hidden_allowlist(),
+        }
+    }
+}
+
+/// Saved selections of one folder: the last used file list plus any number
+/// of named presets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceSelection {
+    pub files: Vec<String>,
+    pub presets: Vec<SelectionPreset>,
+    /// When the folder's selection was last saved or loaded, in seconds
+    /// since the Unix epoch; 0 when unknown
+    pub last_used: u64,
+}
+
+/// Folder from the selection history, for the recent folders list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFolder {
+    pub path: String,
+    pub last_used: u64,
+}
+
+/// Named selection saved for a folder, e.g. "backend only"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelectionPreset {
+    pub name: String,
+    /// Fixed file list, unused when `patterns` is set
+    pub files: Vec<String>,
+    /// Globs expanded against the folder each time the preset is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patterns: Option<SelectionPatterns>,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl SelectionPreset {
+    /// Files selected by the preset, expanding its patterns against a scan
+    /// of `folder_path`
+    pub fn resolve_files(&self, folder_path: &str, options: &ScanOptions) -> Result<Vec<String>, String> {
+        match &self.patterns {
+            Some(patterns) => patterns.resolve(folder_path, options),
+            None => Ok(self.files.clone()),
+        }
+    }
+}
+
+/// Name given to the file list migrated from the single-list format
+const LEGACY_PRESET_NAME: &str = "Default";
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn load_settings() -> Result<SettingsData, String> {
+    match SETTINGS_SCHEMA.load(&config_dir()?)? {
+        Some(value) => Ok(deserialize_lenient(value)),
+        None => Ok(SettingsData::default()),
+    }
+}
+
+/// Scan options for `dir_path`: the user's settings with the project config
+/// found at `dir_path` or a parent layered on top
+pub fn resolve_scan_options(dir_path: &str) -> Result<ScanOptions, String> {
+    let settings = load_settings()?;
+    let defaults = ScanOptions {
+        max_depth: settings.max_scan_depth,
+        symlinks: settings.scan_symlinks,
+        include_hidden: settings.show_hidden,
+        hidden_allowlist: settings.hidden_allowlist,
+        ..ScanOptions::default()
+    };
+    Ok(match discover_project_config(std::path::Path::new(dir_path))? {
+        Some((_, config)) => config.scan_options(defaults),
+        None => defaults,
+    })
+}
+
+/// Generation options for `paths`: the given options, else the defaults of
+/// the project config found above the files. Without explicit limits, the
+/// settings limits apply with the project's limits layered on top.
+pub fn resolve_generate_options(
+    paths: &[String],
+    options: Option<GenerateOptions>,
+) -> Result<GenerateOptions, String> {
+    let project = match common_ancestor(paths) {
+        Some(dir) => discover_project_config(&dir)?.map(|(_, config)| config),
+        None => None,
+    };
+    let mut options = match (options, &project) {
+        (Some(options), _) => options,
+        (None, Some(project)) => project.generate_options(GenerateOptions::default()),
+        (None, None) => GenerateOptions::default(),
+    };
+    if options.limits.is_none() {
+        let limits = load_settings().map(|s| s.file_limits).unwrap_or_default();
+        options.limits = Some(match &project {
+            Some(project) => project.limits(limits),
+            None => limits,
+        });
+    }
+    Ok(options)
+}
+
+pub fn save_settings(settings: &SettingsData) -> Result<(), String> {
+    let value = serde_json::to_value(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    SETTINGS_SCHEMA.update(&config_dir()?, |_| Ok((value, ())))
+}
+
+/// Modify the settings under the file lock and save them
+pub(crate) fn update_settings<R>(
+    update: impl FnOnce(&mut SettingsData) -> Result<R, String>,
+) -> Result<R, String> {
+    SETTINGS_SCHEMA.update(&config_dir()?, |value| {
+        let mut settings = value.map(deserialize_lenient).unwrap_or_default();
+        let result = update(&mut settings)?;
+        let value = serde_json::to_value(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        Ok((value, result))
+    })
+}
+
+/// All saved selections, keyed by normalized folder path
+pub fn load_selection_history() -> Result<BTreeMap<String, SourceSelection>, String> {
+    match SELECTIONS_SCHEMA.load(&data_dir()?)? {
+        Some(value) => parse_history(value),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+fn parse_history(mut value: serde_json::Value) -> Result<BTreeMap<String, SourceSelection>, String> {
+    let folders = match value.get_mut("folders").map(serde_json::Value::take) {
+        Some(serde_json::Value::Object(folders)) => folders,
+        Some(serde_json::Value::Null) | None => return Ok(BTreeMap::new()),
+        Some(_) => return Err("Failed to parse history: 'folders' is not an object".to_string()),
+    };
+    // A bad entry only costs that folder, not the whole history
+    let mut history = BTreeMap::new();
+    for (folder, mut entry) in folders {
+        if !entry.is_object() {
+            eprintln!("Warning: ignoring invalid selection of '{}'", folder);
+            continue;
+        }
+        if let Some(presets) = entry.get_mut("presets").and_then(|p| p.as_array_mut()) {
+            presets.retain(|preset| {
+                let valid = serde_json::from_value::<SelectionPreset>(preset.clone()).is_ok();
+                if !valid {
+                    eprintln!("Warning: ignoring invalid preset of '{}'", folder);
+                }
+                valid
+            });
+        }
+        history.insert(folder, deserialize_lenient(entry));
+    }
+    Ok(history)
+}
+
+/// Modify the selection history under the file lock and save it
+pub(crate) fn update_history<R>(
+    update: impl FnOnce(&mut BTreeMap<String, SourceSelection>) -> Result<R, String>,
+) -> Result<R, String> {
+    SELECTIONS_SCHEMA.update(&data_dir()?, |value| {
+        let mut history = match value {
+            Some(value) => parse_history(value)?,
+            None => BTreeMap::new(),
+        };
+        let result = update(&mut history)?;
+        
+        // Keep only the 50 most recently used folders
+        const MAX_HISTORY_SIZE: usize = 50;
+        if history.len() > MAX_HISTORY_SIZE {
+            let mut by_age: Vec<(u64, String)> = history
+                .iter()
+                .map(|(folder, selection)| (selection.last_used, folder.clone()))
+                .collect();
+            by_age.sort();
+            for (_, folder) in by_age.into_iter().take(history.len() - MAX_HISTORY_SIZE) {
+                history.remove(&folder);
+            }
+        }
+        
+        let folders = serde_json::to_value(&history)
+            .map_err(|e| format!("Failed to serialize history: {}", e))?;
+        Ok((json!({ "folders": folders }), result))
+    })
+}
+
+/// Normalize the path for storage and comparison (convert to canonical path if possible)
+fn normalize_folder(folder_path: &str) -> String {
+    std::path::Path::new(folder_path)
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.to_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| folder_path.to_string())
+}
+
+/// Last file list of a folder. Loading counts as a use of the folder, so it
+/// stays in the history and moves up the recent folders list.
+pub fn load_selections(folder_path: &str) -> Result<Vec<String>, String> {
+    let folder = normalize_folder(folder_path);
+    
+    // Folders without saved selections are not added to the history
+    if !load_selection_history()?.contains_key(&folder) {
+        return Ok(Vec::new());
+    }
+    
+    update_history(|history| {
+        Ok(match history.get_mut(&folder) {
+            Some(selection) => {
+                selection.last_used = now_secs();
+                selection.files.clone()
+            }
+            None => Vec::new(),
+        })
+    })
+}
+
+pub fn save_selections(folder_path: &str, files: Vec<String>) -> Result<(), String> {
+    update_history(|history| {
+        let selection = history.entry(normalize_folder(folder_path)).or_default();
+        selection.files = files;
+        selection.last_used = now_secs();
+        Ok(())
+    })
+}
+
+pub fn list_presets(folder_path: &str) -> Result<Vec<SelectionPreset>, String> {
+    let history = load_selection_history()?;
+    
+    Ok(history
+        .get(&normalize_folder(folder_path))
+        .map(|s| s.presets.clone())
+        .unwrap_or_default())
+}
+
+/// Save `files`, or `patterns` when given, under a new preset name
+pub fn create_preset(
+    folder_path: &str,
+    name: &str,
+    files: Vec<String>,
+    patterns: Option<SelectionPatterns>,
+) -> Result<SelectionPreset, String> {
+    let name = validate_preset_name(name)?;
+    let files = validate_patterns(files, &patterns)?;
+    update_history(|history| {
+        let selection = history.entry(normalize_folder(folder_path)).or_default();
+        
+        if selection.presets.iter().any(|p| p.name == name) {
+            return Err(format!("Preset '{}' already exists", name));
+        }
+        
+        let now = now_secs();
+        let preset = SelectionPreset { name, files, patterns, created_at: now, updated_at: now };
+        selection.presets.push(preset.clone());
+        selection.last_used = now;
+        selection.presets.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(preset)
+    })
+}
+
+/// Replace the files or patterns of an existing preset
+pub fn update_preset(
+    folder_path: &str,
+    name: &str,
+    files: Vec<String>,
+    patterns: Option<SelectionPatterns>,
+) -> Result<SelectionPreset, String> {
+    let files = validate_patterns(files, &patterns)?;
+    update_history(|history| {
+        let preset = find_preset(history, folder_path, name)?;
+        preset.files = files;
+        preset.patterns = patterns;
+        preset.updated_at = now_secs();
+        let preset = preset.clone();
+        touch_folder(history, folder_path);
+        Ok(preset)
+    })
+}
+
+pub fn rename_preset(folder_path: &str, name: &str, new_name: &str) -> Result<SelectionPreset, String> {
+    let new_name = validate_preset_name(new_name)?;
+    update_history(|history| {
+        let taken = history
+            .get(&normalize_folder(folder_path))
+            .is_some_and(|s| s.presets.iter().any(|p| p.name == new_name && p.name != name));
+        if taken {
+            return Err(format!("Preset '{}' already exists", new_name));
+        }
+        
+        let preset = find_preset(history, folder_path, name)?;
+        preset.name = new_name;
+        preset.updated_at = now_secs();
+        let preset = preset.clone();
+        if let Some(selection) = history.get_mut(&normalize_folder(folder_path)) {
+            selection.presets.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        Ok(preset)
+    })
+}
+
+pub fn delete_preset(folder_path: &str, name: &str) -> Result<(), String> {
+    update_history(|history| {
+        let selection = history
+            .get_mut(&normalize_folder(folder_path))
+            .ok_or_else(|| format!("Preset '{}' not found", name))?;
+        
+        let before = selection.presets.len();
+        selection.presets.retain(|p| p.name != name);
+        if selection.presets.len() == before {
+            return Err(format!("Preset '{}' not found", name));
+        }
+        Ok(())
+    })
+}
+
+/// Files of a preset, which also become the folder's last selection.
+/// Pattern presets are expanded against a scan with `options`.
+pub fn load_preset(folder_path: &str, name: &str, options: &ScanOptions) -> Result<Vec<String>, String> {
+    // Resolve before taking the lock: expanding patterns scans the folder
+    let mut history = load_selection_history()?;
+    let files = find_preset(&mut history, folder_path, name)?.resolve_files(folder_path, options)?;
+    update_history(|history| {
+        if let Some(selection) = history.get_mut(&normalize_folder(folder_path)) {
+            selection.files = files.clone();
+            selection.last_used = now_secs();
+        }
+        Ok(files)
+    })
+}
+
+/// Drop files that no longer exist from the folder's last selection and its
+/// fixed-list presets. Returns the dropped paths.
+pub fn prune_selections(folder_path: &str) -> Result<Vec<String>, String> {
+    let exists = |path: &String| std::path::Path::new(path).exists();
+    let folder = normalize_folder(folder_path);
+    
+    // Only take the lock and rewrite the file when something is missing
+    let stale = load_selection_history()?.get(&folder).is_some_and(|selection| {
+        !selection.files.iter().all(exists)
+            || !selection.presets.iter().all(|p| p.files.iter().all(exists))
+    });
+    if !stale {
+        return Ok(Vec::new());
+    }
+    
+    update_history(|history| {
+        let mut removed = Vec::new();
+        if let Some(selection) = history.get_mut(&folder) {
+            let lists = std::iter::once(&mut selection.files)
+                .chain(selection.presets.iter_mut().map(|p| &mut p.files));
+            for files in lists {
+                let (kept, missing): (Vec<_>, Vec<_>) = std::mem::take(files).into_iter().partition(exists);
+                *files = kept;
+                removed.extend(missing);
+            }
+        }
+        removed.sort();
+        removed.dedup();
+        Ok(removed)
+    })
+}
+
+/// Folders from the selection history that still exist, most recently used first
+pub fn recent_folders() -> Result<Vec<RecentFolder>, String> {
+    let mut folders: Vec<RecentFolder> = load_selection_history()?
+        .into_iter()
+        .filter(|(folder, _)| std::path::Path::new(folder).is_dir())
+        .map(|(path, selection)| RecentFolder { path, last_used: selection.last_used })
+        .collect();
+    folders.sort_by_key(|f| std::cmp::Reverse(f.last_used));
+    Ok(folders)
+}
+
+fn touch_folder(history: &mut BTreeMap<String, SourceSelection>, folder_path: &str) {
+    if let Some(selection) = history.get_mut(&normalize_folder(folder_path)) {
+        selection.last_used = now_secs();
+    }
+}
+
+fn find_preset<'a>(
+    history: &'a mut BTreeMap<String, SourceSelection>,
+    folder_path: &str,
+    name: &str,
+) -> Result<&'a mut SelectionPreset, String> {
+    history
+        .get_mut(&normalize_folder(folder_path))
+        .and_then(|s| s.presets.iter_mut().find(|p| p.name == name))
+        .ok_or_else(|| format!("Preset '{}' not found", name))
+}
+
+/// Check the globs up front; pattern presets keep no fixed file list
+fn validate_patterns(files: Vec<String>, patterns: &Option<SelectionPatterns>) -> Result<Vec<String>, String> {
+    match patterns {
+        Some(patterns) => patterns.filter().map(|_| Vec::new()),
+        None => Ok(files),
+    }
+}
+
+fn validate_preset_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Preset name must not be empty".to_string());
+    }
+    Ok(name.to_string())
+}
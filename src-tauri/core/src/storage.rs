@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+/// Overrides both the config and the data directory, e.g. for portable installs
+pub const CONFIG_DIR_ENV: &str = "FILE_EXTRACTOR_CONFIG_DIR";
+
+const APP_DIR_NAME: &str = "file-extractor";
+
+/// Directory used before storage followed platform conventions
+const LEGACY_DIR_NAME: &str = ".source_processor";
+
+/// Files of the legacy directory that belong in the config directory; the
+/// rest (selection history and its backups) go to the data directory
+const CONFIG_FILE_PREFIX: &str = "settings.json";
+
+static LEGACY_MIGRATION: Once = Once::new();
+
+/// Where `settings.json` lives: `$XDG_CONFIG_HOME/file-extractor` on Linux,
+/// `~/Library/Application Support/file-extractor` on macOS and
+/// `%APPDATA%\file-extractor` on Windows
+pub fn config_dir() -> Result<PathBuf, String> {
+    app_dir(dirs::config_dir(), "config")
+}
+
+/// Where the selection history lives: `$XDG_DATA_HOME/file-extractor` on
+/// Linux, the same directory as the config elsewhere
+pub fn data_dir() -> Result<PathBuf, String> {
+    app_dir(dirs::data_dir(), "data")
+}
+
+fn app_dir(platform_dir: Option<PathBuf>, kind: &str) -> Result<PathBuf, String> {
+    let dir = match std::env::var_os(CONFIG_DIR_ENV).filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            LEGACY_MIGRATION.call_once(migrate_legacy_dir);
+            platform_dir
+                .ok_or_else(|| format!("Cannot find the {} directory", kind))?
+                .join(APP_DIR_NAME)
+        }
+    };
+
+    // Create directory if it doesn't exist
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {} directory: {}", kind, e))?;
+
+    Ok(dir)
+}
+
+/// Move the contents of `~/.source_processor` to the platform directories,
+/// once, without overwriting anything already there
+fn migrate_legacy_dir() {
+    let Some(legacy) = dirs::home_dir().map(|home| home.join(LEGACY_DIR_NAME)) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&legacy) else {
+        return;
+    };
+    let (Some(config), Some(data)) = (dirs::config_dir(), dirs::data_dir()) else {
+        return;
+    };
+    let (config, data) = (config.join(APP_DIR_NAME), data.join(APP_DIR_NAME));
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let target_dir = if name.to_string_lossy().starts_with(CONFIG_FILE_PREFIX) { &config } else { &data };
+        let target = target_dir.join(&name);
+        if target.exists() {
+            continue;
+        }
+        if let Err(e) = fs::create_dir_all(target_dir).and_then(|_| move_file(&entry.path(), &target)) {
+            eprintln!("Warning: Failed to migrate {:?} to {:?}: {}", entry.path(), target, e);
+        }
+    }
+
+    // Only succeeds once everything has been moved
+    if fs::remove_dir(&legacy).is_ok() {
+        eprintln!("Migrated settings from {:?} to {:?} and {:?}", legacy, config, data);
+    }
+}
+
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    // Rename fails across file systems; fall back to copy and delete
+    fs::rename(from, to).or_else(|_| fs::copy(from, to).and_then(|_| fs::remove_file(from)))
+}
@@ -0,0 +1,350 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Upgrades the contents of a file from one schema version to the next
+pub(crate) type Migration = fn(Value) -> Result<Value, String>;
+
+/// JSON file in the settings directory with a `version` key.
+///
+/// Writes go through a temporary file renamed over the original, so a crash
+/// never leaves a half-written file, and the previous contents are kept as
+/// `<file>.bak` to recover from if the file is corrupted anyway.
+/// Read-modify-write cycles hold an advisory lock on `<file>.lock` so app
+/// windows and CLI runs don't lose each other's changes.
+pub(crate) struct Schema {
+    pub file_name: &'static str,
+    /// Current version; files without a `version` key are version 1
+    pub version: u64,
+    /// `migrations[i]` upgrades version `i + 1` to `i + 2`
+    pub migrations: &'static [Migration],
+}
+
+/// Contents read from disk and upgraded to the current version
+struct Loaded {
+    value: Value,
+    /// Version the file was written with
+    version: u64,
+    /// The file was unreadable and the value comes from the backup
+    recovered: bool,
+}
+
+impl Schema {
+    /// Current contents, upgraded in memory. Returns `None` when the file
+    /// does not exist.
+    pub fn load(&self, dir: &Path) -> Result<Option<Value>, String> {
+        Ok(self.read(dir)?.map(|loaded| loaded.value))
+    }
+
+    /// Read the file, let `update` produce the new contents and write them,
+    /// all under an exclusive lock. Upgrading an old file first keeps a copy
+    /// of it as `<file>.v<N>.bak`.
+    pub fn update<R>(
+        &self,
+        dir: &Path,
+        update: impl FnOnce(Option<Value>) -> Result<(Value, R), String>,
+    ) -> Result<R, String> {
+        let _lock = self.lock(dir)?;
+        let path = dir.join(self.file_name);
+
+        let current = match self.read(dir) {
+            Ok(current) => current,
+            Err(e) if path.exists() => {
+                // Corrupt and so is the backup: keep it aside and start over
+                let aside = self.set_aside(dir)?;
+                eprintln!("Warning: {}, moved it to {:?} and starting over", e, aside);
+                None
+            }
+            Err(e) => return Err(e),
+        };
+
+        let value = match current {
+            Some(loaded) => {
+                if loaded.recovered {
+                    let aside = self.set_aside(dir)?;
+                    eprintln!("Warning: {} was corrupt, moved it to {:?} and recovered the backup", self.file_name, aside);
+                } else if loaded.version < self.version {
+                    let backup = dir.join(format!("{}.v{}.bak", self.file_name, loaded.version));
+                    fs::copy(&path, &backup)
+                        .map_err(|e| format!("Failed to back up {}: {}", self.file_name, e))?;
+                }
+                Some(loaded.value)
+            }
+            None => None,
+        };
+
+        let (value, result) = update(value)?;
+        self.write(dir, value)?;
+        Ok(result)
+    }
+
+    fn read(&self, dir: &Path) -> Result<Option<Loaded>, String> {
+        let path = dir.join(self.file_name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let (value, recovered) = match read_json(&path) {
+            Ok(value) => (value, false),
+            Err(e) => match read_json(&self.backup_path(dir)) {
+                Ok(value) => {
+                    eprintln!("Warning: {}, using the last good backup", e);
+                    (value, true)
+                }
+                Err(_) => return Err(format!("Failed to parse {}: {}", self.file_name, e)),
+            },
+        };
+
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(1).max(1);
+        if version > self.version {
+            // Written by a newer release: read what we understand
+            eprintln!(
+                "Warning: {} has version {}, this release supports up to {}",
+                self.file_name, version, self.version
+            );
+            return Ok(Some(Loaded { value, version, recovered }));
+        }
+
+        let mut value = value;
+        for migration in &self.migrations[(version - 1) as usize..] {
+            value = migration(value)?;
+        }
+        Ok(Some(Loaded { value, version, recovered }))
+    }
+
+    /// Atomically replace the file with `value`, stamped with the current version
+    fn write(&self, dir: &Path, mut value: Value) -> Result<(), String> {
+        if let Value::Object(map) = &mut value {
+            map.insert("version".to_string(), Value::from(self.version));
+        }
+        let content = serde_json::to_string_pretty(&value)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.file_name, e))?;
+
+        let path = dir.join(self.file_name);
+        let temp = dir.join(format!(".{}.{}.tmp", self.file_name, std::process::id()));
+        let written = File::create(&temp)
+            .and_then(|mut file| {
+                file.write_all(content.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| {
+                // Keep the current contents as the last good backup
+                if path.exists() {
+                    fs::copy(&path, self.backup_path(dir))?;
+                }
+                fs::rename(&temp, &path)
+            });
+
+        written.map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            format!("Failed to write {}: {}", self.file_name, e)
+        })
+    }
+
+    fn lock(&self, dir: &Path) -> Result<File, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(format!("{}.lock", self.file_name)))
+            .map_err(|e| format!("Failed to open lock for {}: {}", self.file_name, e))?;
+        // Released when the file is closed
+        file.lock()
+            .map_err(|e| format!("Failed to lock {}: {}", self.file_name, e))?;
+        Ok(file)
+    }
+
+    fn backup_path(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("{}.bak", self.file_name))
+    }
+
+    /// Rename an unreadable file out of the way so it is not overwritten
+    fn set_aside(&self, dir: &Path) -> Result<PathBuf, String> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let aside = dir.join(format!("{}.corrupt-{}", self.file_name, stamp));
+        fs::rename(dir.join(self.file_name), &aside)
+            .map_err(|e| format!("Failed to move corrupt {} aside: {}", self.file_name, e))?;
+        Ok(aside)
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("{:?} is not valid JSON: {}", path, e))
+}
+
+/// Deserialize an object field by field over `T::default()`, so a missing or
+/// invalid field falls back to its default instead of failing the whole file
+pub(crate) fn deserialize_lenient<T: Default + Serialize + DeserializeOwned>(value: Value) -> T {
+    if let Ok(parsed) = serde_json::from_value(value.clone()) {
+        return parsed;
+    }
+
+    let Ok(mut merged) = serde_json::to_value(T::default()) else {
+        return T::default();
+    };
+    if let (Value::Object(fields), Value::Object(_)) = (value, &merged) {
+        for (key, field) in fields {
+            let mut candidate = merged.clone();
+            candidate[key.as_str()] = field;
+            if serde_json::from_value::<T>(candidate.clone()).is_ok() {
+                merged = candidate;
+            } else {
+                eprintln!("Warning: ignoring invalid field '{}'", key);
+            }
+        }
+    }
+    serde_json::from_value(merged).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::settings::{load_selection_history, load_settings, save_selections, save_settings, SettingsData};
+    use crate::storage::CONFIG_DIR_ENV;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    // The storage directory comes from the environment, shared by all tests
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_storage_dir(test: impl FnOnce(&Path)) {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var(CONFIG_DIR_ENV, dir.path());
+        test(dir.path());
+        std::env::remove_var(CONFIG_DIR_ENV);
+    }
+
+    fn set_aside_files(dir: &Path, file_name: &str) -> Vec<String> {
+        let prefix = format!("{}.corrupt-", file_name);
+        fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with(&prefix))
+            .collect()
+    }
+
+    #[test]
+    fn corrupt_file_recovers_from_backup() {
+        with_storage_dir(|dir| {
+            fs::write(dir.join("settings.json"), "{ \"dark_mode\": tru").unwrap();
+            fs::write(dir.join("settings.json.bak"), r#"{ "version": 2, "dark_mode": true, "last_folder": "/work" }"#)
+                .unwrap();
+
+            let settings = load_settings().unwrap();
+            assert!(settings.dark_mode);
+            assert_eq!(settings.last_folder, "/work");
+
+            // The next write keeps the corrupt file aside and saves good contents
+            save_settings(&settings).unwrap();
+            assert_eq!(set_aside_files(dir, "settings.json").len(), 1);
+            let saved: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(dir.join("settings.json")).unwrap()).unwrap();
+            assert_eq!(saved["dark_mode"], true);
+            assert_eq!(saved["version"], 2);
+        });
+    }
+
+    #[test]
+    fn corrupt_file_and_backup_start_over() {
+        with_storage_dir(|dir| {
+            fs::write(dir.join("settings.json"), "not json").unwrap();
+            fs::write(dir.join("settings.json.bak"), "not json either").unwrap();
+
+            assert!(load_settings().unwrap_err().contains("settings.json"));
+
+            let settings = SettingsData { last_folder: "/fresh".to_string(), ..SettingsData::default() };
+            save_settings(&settings).unwrap();
+            let aside = set_aside_files(dir, "settings.json");
+            assert_eq!(aside.len(), 1);
+            assert_eq!(fs::read_to_string(dir.join(&aside[0])).unwrap(), "not json");
+            assert_eq!(load_settings().unwrap().last_folder, "/fresh");
+        });
+    }
+
+    #[test]
+    fn v1_selections_upgrade_and_keep_a_copy() {
+        with_storage_dir(|dir| {
+            let v1 = r#"{ "/projects/a": { "files": ["/projects/a/main.rs", "/projects/a/lib.rs"] } }"#;
+            fs::write(dir.join("folder_selections.json"), v1).unwrap();
+
+            // Reading upgrades in memory only
+            let history = load_selection_history().unwrap();
+            assert_eq!(history["/projects/a"].files, ["/projects/a/main.rs", "/projects/a/lib.rs"]);
+            assert_eq!(history["/projects/a"].presets[0].files, history["/projects/a"].files);
+            assert!(!dir.join("folder_selections.json.v1.bak").exists());
+
+            save_selections("/projects/b", vec!["/projects/b/app.py".to_string()]).unwrap();
+            assert_eq!(fs::read_to_string(dir.join("folder_selections.json.v1.bak")).unwrap(), v1);
+
+            let saved: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(dir.join("folder_selections.json")).unwrap()).unwrap();
+            assert_eq!(saved["version"], 2);
+            assert!(saved["folders"]["/projects/a"].is_object());
+            let history = load_selection_history().unwrap();
+            assert_eq!(history["/projects/a"].files.len(), 2);
+            assert_eq!(history["/projects/b"].files, ["/projects/b/app.py"]);
+        });
+    }
+
+    #[test]
+    fn v1_selections_keep_presets_of_current_entries() {
+        with_storage_dir(|dir| {
+            let v1 = r#"{
+                "/projects/a": { "files": ["/projects/a/main.rs"] },
+                "/projects/c": {
+                    "files": ["/projects/c/api.rs"],
+                    "presets": [{ "name": "backend", "files": ["/projects/c/api.rs"], "created_at": 1 }],
+                    "last_used": 5
+                }
+            }"#;
+            fs::write(dir.join("folder_selections.json"), v1).unwrap();
+
+            let history = load_selection_history().unwrap();
+            assert_eq!(history["/projects/a"].presets[0].name, "Default");
+            let current = &history["/projects/c"];
+            assert_eq!(current.presets.len(), 1);
+            assert_eq!(current.presets[0].name, "backend");
+            assert_eq!(current.presets[0].created_at, 1);
+            assert_eq!(current.presets[0].updated_at, 0);
+            assert_eq!(current.last_used, 5);
+        });
+    }
+
+    #[test]
+    fn invalid_selection_entries_are_skipped() {
+        with_storage_dir(|dir| {
+            let v2 = r#"{ "version": 2, "folders": {
+                "/bad": { "presets": [] },
+                "/worse": "not an entry",
+                "/projects/a": {
+                    "files": ["/projects/a/main.rs"],
+                    "presets": [
+                        { "name": "backend", "files": ["/projects/a/main.rs"] },
+                        { "name": 7 }
+                    ],
+                    "last_used": "yesterday"
+                }
+            } }"#;
+            fs::write(dir.join("folder_selections.json"), v2).unwrap();
+
+            let history = load_selection_history().unwrap();
+            assert!(!history.contains_key("/worse"));
+            assert!(history["/bad"].files.is_empty());
+            let entry = &history["/projects/a"];
+            assert_eq!(entry.files, ["/projects/a/main.rs"]);
+            assert_eq!(entry.presets.len(), 1);
+            assert_eq!(entry.presets[0].name, "backend");
+            assert_eq!(entry.last_used, 0);
+        });
+    }
+}
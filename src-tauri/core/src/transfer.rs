@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use crate::settings::{
+    load_selection_history, load_settings, now_secs, update_history, update_settings,
+    ApiServerSettings, SelectionPreset, SettingsData, SourceSelection,
+};
+
+/// Version of the export format; bumped when its shape changes incompatibly
+const EXPORT_VERSION: u64 = 1;
+
+/// Prefix of folder paths stored relative to the home directory
+const HOME_PREFIX: &str = "~/";
+
+/// Portable copy of the settings and the selection history, for moving to
+/// another machine or sharing with a team.
+///
+/// Folders under the home directory are stored as `~/...` and selected
+/// files as paths relative to their folder, with `/` separators. The API
+/// server settings belong to the machine: they are neither exported nor
+/// imported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsExport {
+    pub format_version: u64,
+    /// Seconds since the Unix epoch
+    pub exported_at: u64,
+    pub settings: SettingsData,
+    pub folders: BTreeMap<String, SourceSelection>,
+}
+
+/// How an import combines with what is already stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Keep local settings and presets, add what is missing
+    #[default]
+    Merge,
+    /// Overwrite the settings and the selection history
+    Replace,
+}
+
+/// Something the import could not take as is, and what was done instead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConflict {
+    /// Folder of the conflicting preset; `None` for settings
+    pub folder: Option<String>,
+    /// Setting key or preset name
+    pub name: String,
+    pub resolution: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub settings_replaced: bool,
+    pub folders_added: usize,
+    pub folders_merged: usize,
+    pub presets_added: usize,
+    pub conflicts: Vec<ImportConflict>,
+    /// Imported folders that do not exist on this machine
+    pub missing_folders: Vec<String>,
+}
+
+/// Current settings and selection history with paths made portable
+pub fn export_settings() -> Result<SettingsExport, String> {
+    let home = dirs::home_dir();
+    let mut settings = load_settings()?;
+    settings.api_server = ApiServerSettings::default();
+    settings.last_folder = to_portable_folder(&settings.last_folder, home.as_deref());
+
+    let folders = load_selection_history()?
+        .into_iter()
+        .map(|(folder, mut selection)| {
+            let root = Path::new(&folder);
+            selection.files = selection.files.iter().map(|f| to_portable_file(f, root)).collect();
+            for preset in &mut selection.presets {
+                preset.files = preset.files.iter().map(|f| to_portable_file(f, root)).collect();
+            }
+            (to_portable_folder(&folder, home.as_deref()), selection)
+        })
+        .collect();
+
+    Ok(SettingsExport { format_version: EXPORT_VERSION, exported_at: now_secs(), settings, folders })
+}
+
+impl SettingsExport {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let export: Self = serde_json::from_str(content)
+            .map_err(|e| format!("Invalid settings export: {}", e))?;
+        if export.format_version > EXPORT_VERSION {
+            return Err(format!(
+                "Settings export has version {}, this release supports up to {}",
+                export.format_version, EXPORT_VERSION
+            ));
+        }
+        Ok(export)
+    }
+
+    /// Expand the portable paths against this machine's home directory
+    fn into_local(mut self) -> Result<Self, String> {
+        let home = dirs::home_dir();
+        self.settings.last_folder = to_local_folder(&self.settings.last_folder, home.as_deref())?;
+        self.folders = self
+            .folders
+            .into_iter()
+            .map(|(folder, mut selection)| {
+                let folder = to_local_folder(&folder, home.as_deref())?;
+                let root = Path::new(&folder);
+                selection.files = to_local_files(&selection.files, root)?;
+                for preset in &mut selection.presets {
+                    preset.files = to_local_files(&preset.files, root)?;
+                }
+                Ok((folder, selection))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(self)
+    }
+}
+
+/// Apply an export to the stored settings and selection history
+pub fn import_settings(export: SettingsExport, mode: ImportMode) -> Result<ImportReport, String> {
+    let export = export.into_local()?;
+    let mut report = ImportReport::default();
+
+    let conflicts = update_settings(|local| {
+        Ok(match mode {
+            ImportMode::Replace => {
+                // The API server belongs to this machine
+                let api_server = std::mem::take(&mut local.api_server);
+                *local = export.settings.clone();
+                local.api_server = api_server;
+                Vec::new()
+            }
+            ImportMode::Merge => merge_settings(local, &export.settings),
+        })
+    })?;
+    report.settings_replaced = mode == ImportMode::Replace;
+    report.conflicts = conflicts;
+
+    report.missing_folders = export
+        .folders
+        .keys()
+        .filter(|folder| !Path::new(folder).is_dir())
+        .cloned()
+        .collect();
+
+    update_history(|history| {
+        if mode == ImportMode::Replace {
+            history.clear();
+        }
+        for (folder, imported) in export.folders {
+            match history.get_mut(&folder) {
+                Some(local) => {
+                    merge_selection(local, imported, &folder, &mut report);
+                    report.folders_merged += 1;
+                }
+                None => {
+                    report.presets_added += imported.presets.len();
+                    report.folders_added += 1;
+                    history.insert(folder, imported);
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(report)
+}
+
+/// Keep the local settings, adding recent sources and reporting the
+/// settings the export would have changed
+fn merge_settings(local: &mut SettingsData, imported: &SettingsData) -> Vec<ImportConflict> {
+    for source in &imported.recent_sources {
+        if !local.recent_sources.contains(source) {
+            local.recent_sources.push(source.clone());
+        }
+    }
+    if local.last_folder.is_empty() {
+        local.last_folder = imported.last_folder.clone();
+    }
+
+    let mut imported = imported.clone();
+    imported.api_server = local.api_server.clone();
+    let (Ok(serde_json::Value::Object(ours)), Ok(serde_json::Value::Object(theirs))) =
+        (serde_json::to_value(&*local), serde_json::to_value(&imported))
+    else {
+        return Vec::new();
+    };
+
+    theirs
+        .into_iter()
+        .filter(|(key, _)| key != "recent_sources" && key != "last_folder")
+        .filter(|(key, value)| ours.get(key) != Some(value))
+        .map(|(key, _)| ImportConflict {
+            folder: None,
+            name: key,
+            resolution: "kept the local value".to_string(),
+        })
+        .collect()
+}
+
+/// Add the imported presets to a folder that already has selections. A
+/// preset whose name is taken by a different one is added under a new name.
+fn merge_selection(local: &mut SourceSelection, imported: SourceSelection, folder: &str, report: &mut ImportReport) {
+    if imported.last_used > local.last_used {
+        local.files = imported.files;
+        local.last_used = imported.last_used;
+    }
+
+    for preset in imported.presets {
+        match local.presets.iter().find(|p| p.name == preset.name) {
+            None => {}
+            Some(existing) if same_selection(existing, &preset) => continue,
+            Some(_) => {
+                let name = unused_name(&local.presets, &preset.name);
+                report.conflicts.push(ImportConflict {
+                    folder: Some(folder.to_string()),
+                    name: preset.name.clone(),
+                    resolution: format!("kept the local preset, imported as '{}'", name),
+                });
+                local.presets.push(SelectionPreset { name, ..preset });
+                report.presets_added += 1;
+                continue;
+            }
+        }
+        local.presets.push(preset);
+        report.presets_added += 1;
+    }
+    local.presets.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+fn same_selection(a: &SelectionPreset, b: &SelectionPreset) -> bool {
+    a.files == b.files
+        && serde_json::to_value(&a.patterns).ok() == serde_json::to_value(&b.patterns).ok()
+}
+
+/// `name (imported)`, numbered if that is taken too
+fn unused_name(presets: &[SelectionPreset], name: &str) -> String {
+    let taken = |candidate: &str| presets.iter().any(|p| p.name == candidate);
+    let mut candidate = format!("{} (imported)", name);
+    let mut n = 2;
+    while taken(&candidate) {
+        candidate = format!("{} (imported {})", name, n);
+        n += 1;
+    }
+    candidate
+}
+
+fn to_portable_folder(folder: &str, home: Option<&Path>) -> String {
+    home.and_then(|home| Path::new(folder).strip_prefix(home).ok())
+        .map(|relative| format!("{}{}", HOME_PREFIX, join_components(relative)))
+        .unwrap_or_else(|| folder.to_string())
+}
+
+fn to_local_folder(folder: &str, home: Option<&Path>) -> Result<String, String> {
+    match (folder.strip_prefix(HOME_PREFIX), home) {
+        (Some(relative), Some(home)) => join_relative(home, relative)
+            .map(|path| path.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Invalid folder in settings export: {}", folder)),
+        _ => Ok(folder.to_string()),
+    }
+}
+
+/// Path relative to the folder when inside it, unchanged otherwise
+fn to_portable_file(file: &str, folder: &Path) -> String {
+    Path::new(file)
+        .strip_prefix(folder)
+        .map(join_components)
+        .unwrap_or_else(|_| file.to_string())
+}
+
+fn to_local_file(file: &str, folder: &Path) -> Result<String, String> {
+    if Path::new(file).is_absolute() {
+        return Ok(file.to_string());
+    }
+    join_relative(folder, file)
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid file in settings export: {}", file))
+}
+
+fn to_local_files(files: &[String], folder: &Path) -> Result<Vec<String>, String> {
+    files.iter().map(|f| to_local_file(f, folder)).collect()
+}
+
+fn join_components(relative: &Path) -> String {
+    relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `base` joined with a `/`-separated relative path, or `None` when a part
+/// is not a plain name (e.g. `..`) and could lead out of `base`
+fn join_relative(base: &Path, relative: &str) -> Option<PathBuf> {
+    let mut path = base.to_path_buf();
+    for part in relative.split('/').filter(|part| !part.is_empty()) {
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => path.push(name),
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_string(path: PathBuf) -> String {
+        path.to_string_lossy().to_string()
+    }
+
+    fn preset(name: &str, files: &[&str]) -> SelectionPreset {
+        SelectionPreset {
+            name: name.to_string(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            ..SelectionPreset::default()
+        }
+    }
+
+    #[test]
+    fn paths_round_trip_through_the_portable_form() {
+        let home = std::env::temp_dir().join("home");
+        let folder = path_string(home.join("projects").join("app"));
+        let file = path_string(home.join("projects").join("app").join("src").join("main.rs"));
+        let outside = path_string(std::env::temp_dir().join("notes.txt"));
+
+        let portable_folder = to_portable_folder(&folder, Some(&home));
+        assert_eq!(portable_folder, "~/projects/app");
+        assert_eq!(to_portable_file(&file, Path::new(&folder)), "src/main.rs");
+        assert_eq!(to_portable_file(&outside, Path::new(&folder)), outside);
+
+        let local_folder = to_local_folder(&portable_folder, Some(&home)).unwrap();
+        assert_eq!(local_folder, folder);
+        assert_eq!(to_local_file("src/main.rs", Path::new(&local_folder)).unwrap(), file);
+        assert_eq!(to_local_file(&outside, Path::new(&local_folder)).unwrap(), outside);
+    }
+
+    #[test]
+    fn parent_segments_are_rejected() {
+        let home = std::env::temp_dir().join("home");
+        assert!(to_local_folder("~/../other", Some(&home)).is_err());
+        assert!(to_local_file("src/../../secret", &home).is_err());
+        assert!(to_local_file("./src/main.rs", &home).is_err());
+    }
+
+    #[test]
+    fn unused_name_numbers_taken_names() {
+        let presets = [preset("backend", &[]), preset("backend (imported)", &[])];
+        assert_eq!(unused_name(&presets[..1], "backend"), "backend (imported)");
+        assert_eq!(unused_name(&presets, "backend"), "backend (imported 2)");
+    }
+
+    #[test]
+    fn merged_presets_keep_the_local_ones() {
+        let mut local = SourceSelection {
+            files: vec!["a.rs".to_string()],
+            presets: vec![preset("backend", &["a.rs"]), preset("docs", &["README.md"])],
+            last_used: 10,
+        };
+        let imported = SourceSelection {
+            files: vec!["b.rs".to_string()],
+            presets: vec![preset("backend", &["b.rs"]), preset("docs", &["README.md"]), preset("ui", &["c.ts"])],
+            last_used: 5,
+        };
+        let mut report = ImportReport::default();
+        merge_selection(&mut local, imported, "/work", &mut report);
+
+        let names: Vec<&str> = local.presets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["backend", "backend (imported)", "docs", "ui"]);
+        assert_eq!(local.presets[0].files, ["a.rs"]);
+        assert_eq!(local.presets[1].files, ["b.rs"]);
+        assert_eq!(local.files, ["a.rs"]);
+        assert_eq!(report.presets_added, 2);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].name, "backend");
+    }
+}
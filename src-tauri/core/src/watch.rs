@@ -0,0 +1,260 @@
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::scan::{validate_directory_path, ScanContext, ScanOptions};
+
+/// How long changes are collected after the first one before being handed
+/// out together, so a checkout or build produces a few batches instead of
+/// thousands of calls
+const BATCH_WINDOW: Duration = Duration::from_millis(200);
+
+/// Change to the scanned tree, in terms of `FileNode` paths
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FsChange {
+    Added { path: String, is_dir: bool },
+    Removed { path: String },
+    Renamed { from: String, to: String },
+}
+
+/// Watches a scanned root and reports the changes a rescan would show.
+/// Entries a scan leaves out (hidden, ignored or beyond the depth limit)
+/// are not reported, and their directories are not watched, so trees like
+/// `node_modules` do not use up the system's watch limit. Directories
+/// reached through symbolic links are not watched either. Watching stops
+/// when the watcher is dropped.
+pub struct TreeWatcher {
+    root: PathBuf,
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+}
+
+impl TreeWatcher {
+    /// Start watching `root`, calling `on_changes` from a background thread
+    pub fn watch(
+        root: &str,
+        options: &ScanOptions,
+        on_changes: impl Fn(Vec<FsChange>) + Send + 'static,
+    ) -> Result<Self, String> {
+        let root = validate_directory_path(root)?;
+        let view = View::new(&root, options)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            match event {
+                Ok(event) => {
+                    let _ = sender.send(event);
+                }
+                Err(e) => eprintln!("Warning: file watcher error: {}", e),
+            }
+        })
+        .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+        watcher
+            .watch(&root, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+        let mut watched = HashSet::from([root.clone()]);
+        watch_subdirs(&mut watcher, &root, &view, &mut watched);
+
+        // The thread only holds a weak reference, so dropping the watcher
+        // still drops the sender and ends the thread
+        let watcher = Arc::new(Mutex::new(watcher));
+        let thread_watcher = Arc::downgrade(&watcher);
+        let base_path = root.clone();
+        let options = options.clone();
+        thread::spawn(move || {
+            let Ok(view) = View::new(&base_path, &options) else {
+                return;
+            };
+            let listed = |path: &Path| view.lists(path);
+
+            // Ends when the watcher, and with it the sender, is dropped
+            while let Ok(event) = receiver.recv() {
+                let mut changes = Vec::new();
+                collect_changes(event, &listed, &mut changes);
+                let deadline = Instant::now() + BATCH_WINDOW;
+                while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+                    match receiver.recv_timeout(left) {
+                        Ok(event) => collect_changes(event, &listed, &mut changes),
+                        Err(_) => break,
+                    }
+                }
+                let changes = coalesce(changes);
+                if changes.is_empty() {
+                    continue;
+                }
+                if let Some(watcher) = thread_watcher.upgrade() {
+                    let mut watcher = watcher.lock().unwrap_or_else(|e| e.into_inner());
+                    update_watches(&mut watcher, &changes, &view, &mut watched);
+                }
+                on_changes(changes);
+            }
+        });
+
+        Ok(Self { root, _watcher: watcher })
+    }
+
+    /// Canonical path of the watched root
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+/// Which paths under the root a scan with the watcher's options would list
+struct View<'a> {
+    ctx: ScanContext<'a>,
+    base_path: &'a Path,
+    max_depth: usize,
+}
+
+impl<'a> View<'a> {
+    fn new(base_path: &'a Path, options: &ScanOptions) -> Result<Self, String> {
+        Ok(Self { ctx: ScanContext::new(base_path, options)?, base_path, max_depth: options.max_depth })
+    }
+
+    fn depth(&self, path: &Path) -> usize {
+        path.strip_prefix(self.base_path).map_or(0, |p| p.components().count())
+    }
+
+    fn lists(&self, path: &Path) -> bool {
+        self.depth(path) <= self.max_depth && self.ctx.is_listed(path)
+    }
+
+    /// Whether a scan would list the entries of the directory at `path`
+    fn lists_entries_of(&self, path: &Path) -> bool {
+        self.depth(path) < self.max_depth && self.ctx.is_listed(path)
+    }
+}
+
+/// Watch the subdirectories of `dir` whose entries a scan would list,
+/// recursively
+fn watch_subdirs(watcher: &mut RecommendedWatcher, dir: &Path, view: &View, watched: &mut HashSet<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            watch_dir(watcher, &entry.path(), view, watched);
+        }
+    }
+}
+
+/// Watch a directory that appeared, along with its subdirectories
+fn watch_dir(watcher: &mut RecommendedWatcher, dir: &Path, view: &View, watched: &mut HashSet<PathBuf>) {
+    if watched.contains(dir) || !view.lists_entries_of(dir) {
+        return;
+    }
+    if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+        eprintln!("Warning: failed to watch {}: {}", dir.display(), e);
+        return;
+    }
+    watched.insert(dir.to_path_buf());
+    watch_subdirs(watcher, dir, view, watched);
+}
+
+/// Stop watching `dir` and everything under it
+fn unwatch_tree(watcher: &mut RecommendedWatcher, dir: &Path, watched: &mut HashSet<PathBuf>) {
+    watched.retain(|path| {
+        if !path.starts_with(dir) {
+            return true;
+        }
+        // Fails when the directory is gone and the watch with it
+        let _ = watcher.unwatch(path);
+        false
+    });
+}
+
+/// Follow directories being added, removed and renamed
+fn update_watches(
+    watcher: &mut RecommendedWatcher,
+    changes: &[FsChange],
+    view: &View,
+    watched: &mut HashSet<PathBuf>,
+) {
+    let is_real_dir = |path: &Path| fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+    for change in changes {
+        match change {
+            FsChange::Added { path, is_dir: true } if is_real_dir(Path::new(path)) => {
+                watch_dir(watcher, Path::new(path), view, watched);
+            }
+            FsChange::Removed { path } => unwatch_tree(watcher, Path::new(path), watched),
+            FsChange::Renamed { from, to } => {
+                unwatch_tree(watcher, Path::new(from), watched);
+                if is_real_dir(Path::new(to)) {
+                    watch_dir(watcher, Path::new(to), view, watched);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_changes(event: Event, listed: &impl Fn(&Path) -> bool, changes: &mut Vec<FsChange>) {
+    let added = |path: &PathBuf| FsChange::Added {
+        path: path.to_string_lossy().to_string(),
+        is_dir: path.is_dir(),
+    };
+    let removed = |path: &PathBuf| FsChange::Removed { path: path.to_string_lossy().to_string() };
+
+    match event.kind {
+        EventKind::Create(_) => changes.extend(event.paths.iter().filter(|p| listed(p)).map(added)),
+        EventKind::Remove(_) => changes.extend(event.paths.iter().filter(|p| listed(p)).map(removed)),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let [from, to] = event.paths.as_slice() else {
+                return;
+            };
+            // Moving in or out of view is an addition or a removal
+            match (listed(from), listed(to)) {
+                (true, true) => changes.push(FsChange::Renamed {
+                    from: from.to_string_lossy().to_string(),
+                    to: to.to_string_lossy().to_string(),
+                }),
+                (true, false) => changes.push(removed(from)),
+                (false, true) => changes.push(added(to)),
+                (false, false) => {}
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            changes.extend(event.paths.iter().filter(|p| listed(p)).map(removed))
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            changes.extend(event.paths.iter().filter(|p| listed(p)).map(added))
+        }
+        // Platforms that cannot tell the two ends of a rename apart
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            for path in event.paths.iter().filter(|p| listed(p)) {
+                changes.push(if path.exists() { added(path) } else { removed(path) });
+            }
+        }
+        // Content changes do not alter the tree
+        _ => {}
+    }
+}
+
+/// Drop duplicates and the separate halves of renames also reported whole
+fn coalesce(changes: Vec<FsChange>) -> Vec<FsChange> {
+    let renames: Vec<(String, String)> = changes
+        .iter()
+        .filter_map(|change| match change {
+            FsChange::Renamed { from, to } => Some((from.clone(), to.clone())),
+            _ => None,
+        })
+        .collect();
+
+    let mut result: Vec<FsChange> = Vec::with_capacity(changes.len());
+    for change in changes {
+        let half_of_rename = match &change {
+            FsChange::Removed { path } => renames.iter().any(|(from, _)| from == path),
+            FsChange::Added { path, .. } => renames.iter().any(|(_, to)| to == path),
+            FsChange::Renamed { .. } => false,
+        };
+        if !half_of_rename && !result.contains(&change) {
+            result.push(change);
+        }
+    }
+    result
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Available output formats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    /// Plain text with `=== PATH ===` / `=== NAME ===` markers
+    #[default]
+    Text,
+}
+
+impl OutputFormat {
+    /// Writer producing this format into `out`
+    pub fn writer<'a, W: Write + 'a>(self, out: W) -> Box<dyn OutputWriter + 'a> {
+        match self {
+            OutputFormat::Text => Box::new(TextWriter::new(out)),
+        }
+    }
+}
+
+/// Destination format of a generated bundle. `generate` drives the writer
+/// with the header, then every selected file, then any extra sections.
+pub trait OutputWriter {
+    fn write_header(&mut self, source_name: &str) -> Result<(), String>;
+
+    /// Write one file, or the reason its content could not be included
+    fn write_file(&mut self, path: &str, name: &str, content: Result<&str, &str>) -> Result<(), String>;
+
+    /// Write an extra section such as a git diff, or the error that prevented it
+    fn write_section(&mut self, title: &str, content: Result<&str, &str>) -> Result<(), String>;
+
+    /// Flush anything still buffered
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Plain text output with `=== PATH ===` / `=== NAME ===` markers
+pub struct TextWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> TextWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> OutputWriter for TextWriter<W> {
+    fn write_header(&mut self, source_name: &str) -> Result<(), String> {
+        writeln!(self.out, "{}", "=".repeat(40))
+            .map_err(|e| format!("Failed to write header: {}", e))?;
+        writeln!(self.out, "{}", source_name)
+            .map_err(|e| format!("Failed to write source name: {}", e))?;
+        writeln!(self.out, "{}", "=".repeat(40))
+            .map_err(|e| format!("Failed to write header: {}", e))?;
+        writeln!(self.out)
+            .map_err(|e| format!("Failed to write newline: {}", e))?;
+        Ok(())
+    }
+
+    fn write_file(&mut self, path: &str, name: &str, content: Result<&str, &str>) -> Result<(), String> {
+        // Write file markers
+        writeln!(self.out, "=== PATH: {} ===", path)
+            .map_err(|e| format!("Failed to write path marker: {}", e))?;
+        writeln!(self.out, "=== NAME: {} ===", name)
+            .map_err(|e| format!("Failed to write name marker: {}", e))?;
+
+        match content {
+            Ok(content) => {
+                write!(self.out, "{}", content)
+                    .map_err(|e| format!("Failed to write content: {}", e))?;
+                writeln!(self.out)
+                    .map_err(|e| format!("Failed to write newline: {}", e))?;
+            }
+            Err(e) => {
+                writeln!(self.out, "[Error: unable to read file - {}]", e)
+                    .map_err(|e| format!("Failed to write error: {}", e))?;
+            }
+        }
+        writeln!(self.out)
+            .map_err(|e| format!("Failed to write newline: {}", e))?;
+        Ok(())
+    }
+
+    fn write_section(&mut self, title: &str, content: Result<&str, &str>) -> Result<(), String> {
+        writeln!(self.out, "=== {} ===", title)
+            .map_err(|e| format!("Failed to write section marker: {}", e))?;
+        match content {
+            Ok(content) => {
+                write!(self.out, "{}", content)
+                    .map_err(|e| format!("Failed to write content: {}", e))?;
+            }
+            Err(e) => {
+                writeln!(self.out, "[Error: {}]", e)
+                    .map_err(|e| format!("Failed to write error: {}", e))?;
+            }
+        }
+        writeln!(self.out)
+            .map_err(|e| format!("Failed to write newline: {}", e))?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.out
+            .flush()
+            .map_err(|e| format!("Failed to write output: {}", e))
+    }
+}
@@ -0,0 +1,36 @@
+use crate::settings::ApiServerSettings;
+use file_extractor_core::{ApiServer, ShutdownHandle};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::thread;
+
+// Handle of the running server and the port/token it was started with
+static RUNNING: Lazy<Mutex<Option<(ShutdownHandle, String)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start, stop or restart the API server so that it matches `settings`.
+/// The token must already be set when the server is enabled.
+pub fn apply_api_server_settings(settings: &ApiServerSettings) -> Result<(), String> {
+    let mut running = RUNNING
+        .lock()
+        .map_err(|_| "API server state is unavailable".to_string())?;
+
+    if let Some((handle, token)) = running.as_ref() {
+        if settings.enabled && handle.port() == settings.port && *token == settings.token {
+            return Ok(());
+        }
+        handle.shutdown();
+        *running = None;
+    }
+
+    if settings.enabled {
+        let server = ApiServer::bind(settings.port, &settings.token)?;
+        *running = Some((server.shutdown_handle()?, settings.token.clone()));
+        thread::spawn(move || {
+            if let Err(e) = server.run() {
+                eprintln!("API server stopped: {}", e);
+            }
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use arboard::Clipboard;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+// Kept alive for the lifetime of the app: on Linux the clipboard contents
+// are served by the owning process and vanish once the handle is dropped
+static CLIPBOARD: Lazy<Mutex<Option<Clipboard>>> = Lazy::new(|| Mutex::new(None));
+
+/// Put text on the system clipboard
+pub fn copy_to_clipboard(text: String) -> Result<(), String> {
+    let mut guard = CLIPBOARD
+        .lock()
+        .map_err(|_| "Clipboard is unavailable".to_string())?;
+
+    if guard.is_none() {
+        *guard = Some(Clipboard::new().map_err(|e| format!("Failed to open clipboard: {}", e))?);
+    }
+
+    guard
+        .as_mut()
+        .expect("clipboard initialized above")
+        .set_text(text)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))
+}
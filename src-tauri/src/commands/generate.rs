@@ -1,19 +1,50 @@
+use file_extractor_core::{generate, resolve_generate_options, FileReport, GenerateOptions, TextWriter};
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
 use scopeguard;
 use crate::GENERATION_RUNNING;
+use super::clipboard::copy_to_clipboard;
 
-// Constants for resource limits
-const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024; // 50MB
+/// Largest output put on the clipboard by default; bigger outputs go to a file
+const DEFAULT_CLIPBOARD_MAX_BYTES: usize = 10 * 1024 * 1024; // 10MB
+
+/// Numbered names tried for the clipboard fallback file before giving up
+const MAX_FALLBACK_FILES: usize = 100;
+
+/// Where the generated output goes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OutputTarget {
+    /// Write to `output_path`
+    #[default]
+    File,
+    /// Copy to the system clipboard, falling back to a file when the output
+    /// exceeds `max_bytes`
+    Clipboard { max_bytes: Option<usize> },
+}
+
+/// Result returned to the frontend after a generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationReport {
+    pub message: String,
+    pub files: Vec<FileReport>,
+    /// Whether the output was copied to the clipboard
+    pub copied_to_clipboard: bool,
+    /// File the output was written to, if any
+    pub output_path: Option<String>,
+}
 
 #[tauri::command]
 pub async fn generate_output(
     selected_paths: Vec<String>,
     source_name: String,
-    output_path: String,
-) -> Result<String, String> {
+    output_path: Option<String>,
+    options: Option<GenerateOptions>,
+    target: Option<OutputTarget>,
+) -> Result<GenerationReport, String> {
     // Check if a generation is already in progress
     if GENERATION_RUNNING.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
         return Err("Generation already in progress, please wait for completion".to_string());
@@ -25,60 +56,108 @@ pub async fn generate_output(
         GENERATION_RUNNING.store(false, Ordering::SeqCst);
     });
     
-    let output_path = Path::new(&output_path);
+    // Layer the project config of the selected files over the user settings
+    let options = resolve_generate_options(&selected_paths, options)?;
     
+    match target.unwrap_or_default() {
+        OutputTarget::File => {
+            let output_path = output_path.ok_or("No output file selected")?;
+            let files = write_to_file(Path::new(&output_path), &selected_paths, &source_name, &options)?;
+            Ok(GenerationReport {
+                message: file_message(Path::new(&output_path)),
+                files,
+                copied_to_clipboard: false,
+                output_path: Some(output_path),
+            })
+        }
+        OutputTarget::Clipboard { max_bytes } => {
+            let mut writer = TextWriter::new(Vec::new());
+            let files = generate(&mut writer, &selected_paths, &source_name, &options)?;
+            let output = writer.into_inner();
+            
+            let max_bytes = max_bytes.unwrap_or(DEFAULT_CLIPBOARD_MAX_BYTES);
+            if output.len() <= max_bytes {
+                copy_to_clipboard(String::from_utf8_lossy(&output).into_owned())?;
+                return Ok(GenerationReport {
+                    message: format!("Output copied to clipboard!\n\n📋 {} files", files.len()),
+                    files,
+                    copied_to_clipboard: true,
+                    output_path: None,
+                });
+            }
+            
+            // Too large for the clipboard: write the already rendered output to a file
+            let fallback = match output_path {
+                Some(path) => {
+                    let path = PathBuf::from(path);
+                    fs::write(&path, &output)
+                        .map_err(|e| format!("Failed to write output file: {}", e))?;
+                    path
+                }
+                None => write_fallback(&source_name, &output)?,
+            };
+            Ok(GenerationReport {
+                message: format!(
+                    "Output too large for the clipboard ({}MB), saved to a file instead.\n\n{}",
+                    output.len() / (1024 * 1024),
+                    file_message(&fallback)
+                ),
+                files,
+                copied_to_clipboard: false,
+                output_path: Some(fallback.to_string_lossy().to_string()),
+            })
+        }
+    }
+}
+
+fn write_to_file(
+    output_path: &Path,
+    selected_paths: &[String],
+    source_name: &str,
+    options: &GenerateOptions,
+) -> Result<Vec<FileReport>, String> {
     // Create output file
-    let mut output_file = fs::File::create(output_path)
+    let output_file = fs::File::create(output_path)
         .map_err(|e| format!("Failed to create output file: {}", e))?;
     
-    // Write header
-    writeln!(output_file, "{}", "=".repeat(40))
-        .map_err(|e| format!("Failed to write header: {}", e))?;
-    writeln!(output_file, "{}", source_name)
-        .map_err(|e| format!("Failed to write source name: {}", e))?;
-    writeln!(output_file, "{}", "=".repeat(40))
-        .map_err(|e| format!("Failed to write header: {}", e))?;
-    writeln!(output_file)
-        .map_err(|e| format!("Failed to write newline: {}", e))?;
-    
-    // Process each selected file
-    for file_path in selected_paths {
-        let path = Path::new(&file_path);
-        
-        // Write file markers
-        writeln!(output_file, "=== PATH: {} ===", file_path)
-            .map_err(|e| format!("Failed to write path marker: {}", e))?;
-        
-        let file_name = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or(&file_path);
-        
-        writeln!(output_file, "=== NAME: {} ===", file_name)
-            .map_err(|e| format!("Failed to write name marker: {}", e))?;
-        
-        // Try to read file with encoding fallback
-        let content = match read_file_with_fallback(&file_path) {
-            Ok(content) => content,
-            Err(e) => {
-                writeln!(output_file, "[Error: unable to read file - {}]", e)
-                    .map_err(|e| format!("Failed to write error: {}", e))?;
-                writeln!(output_file)
-                    .map_err(|e| format!("Failed to write newline: {}", e))?;
-                continue;
-            }
+    let mut writer = TextWriter::new(BufWriter::new(output_file));
+    generate(&mut writer, selected_paths, source_name, options)
+}
+
+/// Write clipboard output that is too large to a new file in the downloads
+/// folder, numbering the name instead of overwriting an existing file
+fn write_fallback(source_name: &str, output: &[u8]) -> Result<PathBuf, String> {
+    let dir = dirs::download_dir().unwrap_or_else(std::env::temp_dir);
+    let name: String = source_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+
+    for n in 1..=MAX_FALLBACK_FILES {
+        let path = match n {
+            1 => dir.join(format!("{}_output.txt", name)),
+            n => dir.join(format!("{}_output ({}).txt", name, n)),
         };
-        
-        // Write file content
-        write!(output_file, "{}", content)
-            .map_err(|e| format!("Failed to write content: {}", e))?;
-        writeln!(output_file)
-            .map_err(|e| format!("Failed to write newline: {}", e))?;
-        writeln!(output_file)
-            .map_err(|e| format!("Failed to write newline: {}", e))?;
+        match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(output)
+                    .map_err(|e| format!("Failed to write output file: {}", e))?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create output file: {}", e)),
+        }
     }
-    
-    Ok(format!(
+    Err(format!(
+        "Failed to create output file: {}_output.txt and {} numbered copies already exist in {}",
+        name,
+        MAX_FALLBACK_FILES - 1,
+        dir.display()
+    ))
+}
+
+fn file_message(output_path: &Path) -> String {
+    format!(
         "File generated successfully!\n\n📄 {}\n📁 {}",
         output_path
             .file_name()
@@ -88,51 +167,5 @@ pub async fn generate_output(
             .parent()
             .and_then(|p| p.to_str())
             .unwrap_or("")
-    ))
-}
-
-/// Try to read file as UTF-8, fallback to Latin-1, or return error message
-fn read_file_with_fallback(file_path: &str) -> Result<String, String> {
-    let path = Path::new(file_path);
-    
-    // Check file size before reading
-    let metadata = fs::metadata(path)
-        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-    
-    if metadata.len() > MAX_FILE_SIZE {
-        return Err(format!(
-            "File too large: {}MB (max: {}MB)",
-            metadata.len() / (1024 * 1024),
-            MAX_FILE_SIZE / (1024 * 1024)
-        ));
-    }
-    
-    // First try: UTF-8
-    match fs::read_to_string(path) {
-        Ok(content) => return Ok(content),
-        Err(e) => {
-            // If it's not a UTF-8 decode error, return immediately
-            if e.kind() != std::io::ErrorKind::InvalidData {
-                return Err(format!("Failed to read file: {}", e));
-            }
-        }
-    }
-    
-    // Second try: Latin-1 (ISO-8859-1)
-    // Read as bytes and convert assuming Latin-1
-    match fs::read(path) {
-        Ok(bytes) => {
-            // Additional safety check for size
-            if bytes.len() as u64 > MAX_FILE_SIZE {
-                return Err(format!(
-                    "File too large: {}MB (max: {}MB)",
-                    bytes.len() / (1024 * 1024),
-                    MAX_FILE_SIZE / (1024 * 1024)
-                ));
-            }
-            let content: String = bytes.iter().map(|&b| b as char).collect();
-            Ok(content)
-        }
-        Err(e) => Err(format!("Failed to read file as binary: {}", e)),
-    }
+    )
 }
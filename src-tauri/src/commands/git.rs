@@ -0,0 +1,12 @@
+use file_extractor_core::{changed_files, ChangedFile, GitChangeSet};
+
+/// List files changed in the git repository containing `dir_path`, limited
+/// to files inside `dir_path`. Reads the repository locally, no remotes are
+/// contacted.
+#[tauri::command]
+pub async fn git_changed_files(
+    dir_path: String,
+    change_set: GitChangeSet,
+) -> Result<Vec<ChangedFile>, String> {
+    changed_files(&dir_path, &change_set)
+}
@@ -1,5 +1,19 @@
 mod scan;
 mod generate;
+mod git;
+mod clipboard;
+mod selections;
+mod watch;
 
-pub use scan::scan_directory;
-pub use generate::generate_output;
+pub use scan::{
+    get_project_config, list_directory, scan_directory, scan_directory_streaming, ScanBatch,
+};
+pub(crate) use scan::scan_options;
+pub use generate::{generate_output, GenerationReport, OutputTarget};
+pub use git::git_changed_files;
+pub use selections::{
+    create_selection_preset, delete_selection_preset, get_recent_folders, list_selection_presets,
+    load_selection_preset, preview_selection_patterns, rename_selection_preset,
+    update_selection_preset,
+};
+pub use watch::{unwatch_directory, watch_directory, FsChangeBatch};
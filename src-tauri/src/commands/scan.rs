@@ -1,186 +1,92 @@
-use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
-use std::fs;
+use file_extractor_core::{
+    discover_project_config, list_directory as list_level, resolve_scan_options, scan_tree,
+    scan_tree_streaming, DirListing, FileNode, ProjectConfig, ScanOptions,
+};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use tauri::{AppHandle, Emitter};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileNode {
-    pub name: String,
-    pub path: String,
-    pub is_dir: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub children: Option<Vec<FileNode>>,
-}
+/// Event carrying the listings of a streaming scan
+const SCAN_BATCH_EVENT: &str = "scan-batch";
 
-/// List of directory names to ignore (like .gitignore)
-const IGNORED_DIRS: &[&str] = &[
-    "node_modules",
-    "venv",
-    ".venv",
-    "env",
-    ".env",
-    "__pycache__",
-    ".pytest_cache",
-    "target",
-    "dist",
-    "build",
-    ".next",
-    ".nuxt",
-    ".cache",
-    ".idea",
-    ".vscode",
-    ".git",
-    ".svn",
-    ".hg",
-    ".DS_Store",
-    "vendor",
-    ".bundle",
-    ".sass-cache",
-    "coverage",
-    ".nyc_output",
-    ".gradle",
-    ".mvn",
-    "bin",
-    "obj",
-    ".vs",
-];
+// Id of the latest streaming scan; older scans stop when it changes
+static CURRENT_SCAN: Lazy<AtomicU64> = Lazy::new(|| AtomicU64::new(0));
 
-fn should_ignore_dir(dir_name: &str) -> bool {
-    IGNORED_DIRS.contains(&dir_name.to_lowercase().as_str())
+/// Payload of `scan-batch` events
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanBatch {
+    pub scan_id: u64,
+    pub listings: Vec<DirListing>,
+    /// Last batch of the scan
+    pub done: bool,
+    pub error: Option<String>,
 }
 
-/// Validate that the path is safe and exists
-fn validate_directory_path(dir_path: &str) -> Result<PathBuf, String> {
-    let path = Path::new(dir_path);
-    
-    // Check if path exists
-    if !path.exists() {
-        return Err(format!("Directory does not exist: {}", dir_path));
+/// Scan options for `dir_path`: the depth comes from the call, else the
+/// project config, else the settings; the project config also adds ignore
+/// rules and may override the symlink policy and hidden entries
+pub(crate) fn scan_options(dir_path: &str, max_depth: Option<usize>) -> Result<ScanOptions, String> {
+    let mut options = resolve_scan_options(dir_path)?;
+    if let Some(max_depth) = max_depth {
+        options.max_depth = max_depth;
     }
-    
-    // Check if path is a directory
-    if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", dir_path));
+    if options.max_depth == 0 {
+        return Err("Maximum depth must be at least 1".to_string());
     }
-    
-    // Canonicalize path to resolve any symlinks and normalize
-    let canonical_path = path.canonicalize()
-        .map_err(|e| format!("Failed to canonicalize path: {}", e))?;
-    
-    Ok(canonical_path)
+    Ok(options)
 }
 
+/// Directories cut off by the depth limit come back with `truncated` set;
+/// scan them directly, or again with a larger `max_depth`, to see more
 #[tauri::command]
-pub async fn scan_directory(dir_path: String) -> Result<Vec<FileNode>, String> {
-    let base_path = validate_directory_path(&dir_path)?;
-    
-    let mut root_nodes = Vec::new();
-    
-    // Recursively scan directory
-    scan_directory_recursive(&base_path, &base_path, &mut root_nodes, 0, 6)?;
-    
-    // Sort: directories first, then files, both alphabetically
-    root_nodes.sort_by(|a, b| {
-        match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-        }
-    });
-    
-    Ok(root_nodes)
+pub async fn scan_directory(dir_path: String, max_depth: Option<usize>) -> Result<Vec<FileNode>, String> {
+    scan_tree(&dir_path, &scan_options(&dir_path, max_depth)?)
 }
 
-fn scan_directory_recursive(
-    base_path: &Path,
-    dir_path: &Path,
-    result: &mut Vec<FileNode>,
-    current_depth: usize,
-    max_depth: usize,
-) -> Result<(), String> {
-    if current_depth >= max_depth {
-        return Ok(());
-    }
-    
-    // Ensure we don't escape from the base directory (additional safety check)
-    // Canonicalize to resolve symlinks
-    let canonical_dir = match dir_path.canonicalize() {
-        Ok(p) => p,
-        Err(_) => {
-            // If we can't canonicalize, skip this directory
-            return Ok(());
-        }
-    };
-    
-    if !canonical_dir.starts_with(base_path) {
-        // Path traversal detected - skip this directory
-        return Ok(());
-    }
-    
-    let entries = match fs::read_dir(dir_path) {
-        Ok(entries) => entries,
-        Err(e) => {
-            // Log permission errors for debugging but continue gracefully
-            eprintln!("Warning: Cannot read directory {:?}: {}", dir_path, e);
-            return Ok(());
-        }
-    };
-    
-    for entry in entries {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        
-        let entry_path = entry.path();
-        
-        // Skip hidden files/directories
-        if entry_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .map(|s| s.starts_with('.'))
-            .unwrap_or(false)
-        {
-            continue;
-        }
-        
-        let name = entry_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| entry_path.to_string_lossy().to_string());
-        
-        let path_str = entry_path.to_string_lossy().to_string();
-        let metadata = match entry.metadata() {
-            Ok(m) => m,
-            Err(_) => continue,
+/// One level of `dir_path` inside the scanned `root_path`, for trees loaded
+/// as folders are expanded
+#[tauri::command]
+pub async fn list_directory(
+    root_path: String,
+    dir_path: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<FileNode>, String> {
+    list_level(&root_path, &dir_path, &scan_options(&root_path, max_depth)?)
+}
+
+/// Start scanning `dir_path` in the background and return the scan id.
+/// Listings arrive as `scan-batch` events; starting another scan cancels
+/// this one.
+#[tauri::command]
+pub async fn scan_directory_streaming(
+    app: AppHandle,
+    dir_path: String,
+    max_depth: Option<usize>,
+) -> Result<u64, String> {
+    let options = scan_options(&dir_path, max_depth)?;
+    let scan_id = CURRENT_SCAN.fetch_add(1, Ordering::SeqCst) + 1;
+
+    thread::spawn(move || {
+        let emit = |listings, done, error| {
+            let batch = ScanBatch { scan_id, listings, done, error };
+            app.emit(SCAN_BATCH_EVENT, batch).is_ok()
         };
-        
-        let is_dir = metadata.is_dir();
-        
-        // Skip ignored directories (like node_modules, venv, etc.)
-        if is_dir && should_ignore_dir(&name) {
-            continue;
+        let result = scan_tree_streaming(&dir_path, &options, |listings| {
+            CURRENT_SCAN.load(Ordering::SeqCst) == scan_id && emit(listings, false, None)
+        });
+        if CURRENT_SCAN.load(Ordering::SeqCst) == scan_id {
+            emit(Vec::new(), true, result.err());
         }
-        
-        if is_dir {
-            let mut children = Vec::new();
-            scan_directory_recursive(base_path, &entry_path, &mut children, current_depth + 1, max_depth)?;
-            
-            result.push(FileNode {
-                name,
-                path: path_str,
-                is_dir: true,
-                children: if children.is_empty() { None } else { Some(children) },
-            });
-        } else {
-            result.push(FileNode {
-                name,
-                path: path_str,
-                is_dir: false,
-                children: None,
-            });
-        }
-    }
-    
-    Ok(())
-}
\ No newline at end of file
+    });
+
+    Ok(scan_id)
+}
+
+/// Project config (`.file-extractor.toml`) found at `dir_path` or a parent
+#[tauri::command]
+pub async fn get_project_config(dir_path: String) -> Result<Option<ProjectConfig>, String> {
+    Ok(discover_project_config(Path::new(&dir_path))?.map(|(_, config)| config))
+}
@@ -0,0 +1,72 @@
+use crate::settings::{
+    create_preset, delete_preset, list_presets, load_preset, recent_folders, rename_preset,
+    update_preset, RecentFolder, SelectionPreset,
+};
+use file_extractor_core::SelectionPatterns;
+use super::scan::scan_options;
+
+#[tauri::command]
+pub fn list_selection_presets(folder_path: String) -> Result<Vec<SelectionPreset>, String> {
+    list_presets(&folder_path)
+}
+
+/// Save a preset from a fixed file list, or from globs when `patterns` is given
+#[tauri::command]
+pub fn create_selection_preset(
+    folder_path: String,
+    name: String,
+    files: Vec<String>,
+    patterns: Option<SelectionPatterns>,
+) -> Result<SelectionPreset, String> {
+    create_preset(&folder_path, &name, files, patterns)
+}
+
+/// Overwrite the files or patterns of an existing preset
+#[tauri::command]
+pub fn update_selection_preset(
+    folder_path: String,
+    name: String,
+    files: Vec<String>,
+    patterns: Option<SelectionPatterns>,
+) -> Result<SelectionPreset, String> {
+    update_preset(&folder_path, &name, files, patterns)
+}
+
+#[tauri::command]
+pub fn rename_selection_preset(
+    folder_path: String,
+    name: String,
+    new_name: String,
+) -> Result<SelectionPreset, String> {
+    rename_preset(&folder_path, &name, &new_name)
+}
+
+#[tauri::command]
+pub fn delete_selection_preset(folder_path: String, name: String) -> Result<(), String> {
+    delete_preset(&folder_path, &name)
+}
+
+/// Files of a preset; they also become the folder's last selection
+#[tauri::command]
+pub async fn load_selection_preset(folder_path: String, name: String) -> Result<Vec<String>, String> {
+    load_preset(&folder_path, &name, &scan_options(&folder_path, None)?)
+}
+
+/// Files the patterns currently expand to, without saving anything
+#[tauri::command]
+pub async fn preview_selection_patterns(
+    folder_path: String,
+    patterns: SelectionPatterns,
+) -> Result<Vec<String>, String> {
+    patterns.resolve(&folder_path, &scan_options(&folder_path, None)?)
+}
+
+/// Folders with saved selections, most recently used first
+#[tauri::command]
+pub fn get_recent_folders(limit: Option<usize>) -> Result<Vec<RecentFolder>, String> {
+    let mut folders = recent_folders()?;
+    if let Some(limit) = limit {
+        folders.truncate(limit);
+    }
+    Ok(folders)
+}
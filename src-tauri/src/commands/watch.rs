@@ -0,0 +1,62 @@
+use file_extractor_core::{prune_selections, FsChange, TreeWatcher};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use super::scan::scan_options;
+
+/// Event carrying the changes under the watched root
+const FS_CHANGES_EVENT: &str = "fs-changes";
+
+// Watcher of the folder open in the file dialog; one at a time
+static WATCHER: Lazy<Mutex<Option<TreeWatcher>>> = Lazy::new(|| Mutex::new(None));
+
+/// Payload of `fs-changes` events
+#[derive(Debug, Clone, Serialize)]
+pub struct FsChangeBatch {
+    pub root: String,
+    pub changes: Vec<FsChange>,
+    /// Paths dropped from the saved selections because they no longer exist
+    pub pruned: Vec<String>,
+}
+
+/// Watch `dir_path` with the same options as its scan, replacing any
+/// previous watch. Changes arrive as `fs-changes` events; saved selections
+/// are pruned of deleted files right away and on every removal.
+#[tauri::command]
+pub async fn watch_directory(app: AppHandle, dir_path: String) -> Result<Vec<String>, String> {
+    let options = scan_options(&dir_path, None)?;
+    let pruned = prune_selections(&dir_path)?;
+
+    let mut watcher = WATCHER
+        .lock()
+        .map_err(|_| "File watcher state is unavailable".to_string())?;
+    // Stop the old watcher before starting the new one
+    *watcher = None;
+
+    let folder = dir_path.clone();
+    *watcher = Some(TreeWatcher::watch(&dir_path, &options, move |changes| {
+        let removes = changes.iter().any(|c| !matches!(c, FsChange::Added { .. }));
+        let pruned = if removes {
+            prune_selections(&folder).unwrap_or_else(|e| {
+                eprintln!("Failed to prune selections of {}: {}", folder, e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        let batch = FsChangeBatch { root: folder.clone(), changes, pruned };
+        let _ = app.emit(FS_CHANGES_EVENT, batch);
+    })?);
+
+    Ok(pruned)
+}
+
+#[tauri::command]
+pub fn unwatch_directory() -> Result<(), String> {
+    let mut watcher = WATCHER
+        .lock()
+        .map_err(|_| "File watcher state is unavailable".to_string())?;
+    *watcher = None;
+    Ok(())
+}
@@ -0,0 +1,174 @@
+pub mod commands;
+pub mod settings;
+pub mod notifications;
+pub mod api_server;
+
+use commands::{
+    create_selection_preset, delete_selection_preset, generate_output, get_project_config,
+    get_recent_folders, git_changed_files, list_directory, list_selection_presets,
+    load_selection_preset, preview_selection_patterns, rename_selection_preset, scan_directory,
+    scan_directory_streaming, unwatch_directory, update_selection_preset, watch_directory,
+};
+use settings::{load_settings, save_settings, load_selections, save_selections, SettingsData};
+use notifications::show_windows_notification;
+use api_server::apply_api_server_settings;
+use file_extractor_core::{
+    discover_project_config, export_settings, generate_token, import_settings, ImportMode,
+    ImportReport, ProjectConfig, SettingsExport,
+};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use once_cell::sync::Lazy;
+
+// ============= GLOBAL STATE =============
+pub(crate) static GENERATION_RUNNING: Lazy<AtomicBool> = Lazy::new(|| AtomicBool::new(false));
+
+#[tauri::command]
+fn get_settings() -> Result<SettingsData, String> {
+    load_settings().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_settings(mut settings: SettingsData) -> Result<(), String> {
+    // Keep the stored API token when the frontend sends none
+    if settings.api_server.token.is_empty() {
+        settings.api_server.token = load_settings()
+            .map(|s| s.api_server.token)
+            .unwrap_or_default();
+    }
+    ensure_api_token(&mut settings)?;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+    apply_api_server_settings(&settings.api_server)
+}
+
+/// Generate the API server token the first time the server is enabled
+fn ensure_api_token(settings: &mut SettingsData) -> Result<(), String> {
+    if settings.api_server.enabled && settings.api_server.token.is_empty() {
+        settings.api_server.token = generate_token()?;
+    }
+    Ok(())
+}
+
+/// Write settings, presets and selection history to a portable JSON file
+#[tauri::command]
+fn export_settings_bundle(path: String) -> Result<(), String> {
+    let export = export_settings()?;
+    let content = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Merge or replace the stored settings with an exported bundle
+#[tauri::command]
+fn import_settings_bundle(path: String, mode: ImportMode) -> Result<ImportReport, String> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let report = import_settings(SettingsExport::parse(&content)?, mode)?;
+
+    // The imported settings may enable the API server
+    let mut settings = load_settings()?;
+    if settings.api_server.token.is_empty() && settings.api_server.enabled {
+        ensure_api_token(&mut settings)?;
+        save_settings(&settings)?;
+    }
+    apply_api_server_settings(&settings.api_server)?;
+    Ok(report)
+}
+
+#[tauri::command]
+async fn get_selections(folder_path: String) -> Result<Vec<String>, String> {
+    let files = load_selections(&folder_path).map_err(|e| e.to_string())?;
+    if !files.is_empty() {
+        return Ok(files);
+    }
+    // Nothing saved yet: start from the project's default selection
+    match discover_project_config(Path::new(&folder_path))? {
+        Some((_, ProjectConfig { selection: Some(patterns), .. })) => {
+            patterns.resolve(&folder_path, &commands::scan_options(&folder_path, None)?)
+        }
+        _ => Ok(files),
+    }
+}
+
+#[tauri::command]
+fn save_selection_history(folder_path: String, files: Vec<String>) -> Result<(), String> {
+    save_selections(&folder_path, files).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn send_notification(app: tauri::AppHandle, title: String, body: String) -> Result<(), String> {
+    // Use the Windows notification system that properly registers the app
+    show_windows_notification(&app, &title, &body)
+}
+
+/// Build and run the Tauri application
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(tauri::generate_handler![
+            scan_directory,
+            list_directory,
+            scan_directory_streaming,
+            watch_directory,
+            unwatch_directory,
+            get_project_config,
+            generate_output,
+            git_changed_files,
+            get_settings,
+            update_settings,
+            export_settings_bundle,
+            import_settings_bundle,
+            get_selections,
+            save_selection_history,
+            list_selection_presets,
+            create_selection_preset,
+            update_selection_preset,
+            rename_selection_preset,
+            delete_selection_preset,
+            load_selection_preset,
+            preview_selection_patterns,
+            get_recent_folders,
+            send_notification
+        ])
+        .setup(|app| {
+            #[cfg(target_os = "windows")]
+            {
+                use tauri::Manager;
+                use tauri::window::{Effect, EffectsBuilder};
+                
+                if let Some(window) = app.get_webview_window("main") {
+                    // Apply Mica effect (Windows 11) - dark mode
+                    // Fallback to Acrylic blur for Windows 10
+                    let effects = EffectsBuilder::new()
+                        .effect(Effect::Mica)
+                        .build();
+                    
+                    if window.set_effects(effects).is_err() {
+                        // Fallback to Acrylic blur if Mica is not available (Windows 10)
+                        let blur_effects = EffectsBuilder::new()
+                            .effect(Effect::Blur)
+                            .build();
+                        let _ = window.set_effects(blur_effects);
+                    }
+                }
+            }
+            
+            // Start the local API server if enabled in settings
+            if let Ok(mut settings) = load_settings() {
+                if settings.api_server.token.is_empty() && ensure_api_token(&mut settings).is_ok() {
+                    let _ = save_settings(&settings);
+                }
+                if let Err(e) = apply_api_server_settings(&settings.api_server) {
+                    eprintln!("Failed to start API server: {}", e);
+                }
+            }
+            
+            Ok(())
+        })
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
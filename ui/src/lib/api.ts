@@ -1,5 +1,22 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { SettingsData, FileNode } from '../types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type {
+  SettingsData,
+  FileNode,
+  ScanBatch,
+  FsChangeBatch,
+  GenerateOptions,
+  GenerationReport,
+  OutputTarget,
+  GitChangeSet,
+  ChangedFile,
+  ProjectConfig,
+  RecentFolder,
+  SelectionPatterns,
+  SelectionPreset,
+  ImportMode,
+  ImportReport,
+} from '../types';
 
 export async function getSettings(): Promise<SettingsData> {
   return await invoke<SettingsData>('get_settings');
@@ -9,22 +26,73 @@ export async function updateSettings(settings: SettingsData): Promise<void> {
   await invoke('update_settings', { settings });
 }
 
-export async function scanDirectory(dirPath: string): Promise<FileNode[]> {
-  return await invoke<FileNode[]>('scan_directory', { dirPath });
+export async function scanDirectory(dirPath: string, maxDepth?: number): Promise<FileNode[]> {
+  return await invoke<FileNode[]>('scan_directory', { dirPath, maxDepth });
+}
+
+export async function listDirectory(
+  rootPath: string,
+  dirPath: string,
+  maxDepth?: number
+): Promise<FileNode[]> {
+  return await invoke<FileNode[]>('list_directory', { rootPath, dirPath, maxDepth });
+}
+
+/**
+ * Scan in the background, calling `onBatch` with listings as they are read.
+ * Resolves to a function that stops listening.
+ */
+export async function scanDirectoryStreaming(
+  dirPath: string,
+  onBatch: (batch: ScanBatch) => void,
+  maxDepth?: number
+): Promise<UnlistenFn> {
+  let scanId: number | null = null;
+  const pending: ScanBatch[] = [];
+  const unlisten = await listen<ScanBatch>('scan-batch', (event) => {
+    if (scanId === null) {
+      pending.push(event.payload);
+    } else if (event.payload.scan_id === scanId) {
+      onBatch(event.payload);
+    }
+  });
+  try {
+    scanId = await invoke<number>('scan_directory_streaming', { dirPath, maxDepth });
+  } catch (e) {
+    unlisten();
+    throw e;
+  }
+  pending.filter((batch) => batch.scan_id === scanId).forEach(onBatch);
+  return unlisten;
+}
+
+export async function getProjectConfig(dirPath: string): Promise<ProjectConfig | null> {
+  return await invoke<ProjectConfig | null>('get_project_config', { dirPath });
 }
 
 export async function generateOutput(
   selectedPaths: string[],
   sourceName: string,
-  outputPath: string
-): Promise<string> {
-  return await invoke<string>('generate_output', {
+  outputPath: string | null,
+  options?: GenerateOptions,
+  target?: OutputTarget
+): Promise<GenerationReport> {
+  return await invoke<GenerationReport>('generate_output', {
     selectedPaths,
     sourceName,
     outputPath,
+    options,
+    target,
   });
 }
 
+export async function gitChangedFiles(
+  dirPath: string,
+  changeSet: GitChangeSet
+): Promise<ChangedFile[]> {
+  return await invoke<ChangedFile[]>('git_changed_files', { dirPath, changeSet });
+}
+
 export async function getSelections(folderPath: string): Promise<string[]> {
   return await invoke<string[]>('get_selections', { folderPath });
 }
@@ -36,3 +104,96 @@ export async function saveSelectionHistory(
   await invoke('save_selection_history', { folderPath, files });
 }
 
+
+export async function listSelectionPresets(folderPath: string): Promise<SelectionPreset[]> {
+  return await invoke<SelectionPreset[]>('list_selection_presets', { folderPath });
+}
+
+export async function createSelectionPreset(
+  folderPath: string,
+  name: string,
+  files: string[],
+  patterns?: SelectionPatterns
+): Promise<SelectionPreset> {
+  return await invoke<SelectionPreset>('create_selection_preset', {
+    folderPath,
+    name,
+    files,
+    patterns,
+  });
+}
+
+export async function updateSelectionPreset(
+  folderPath: string,
+  name: string,
+  files: string[],
+  patterns?: SelectionPatterns
+): Promise<SelectionPreset> {
+  return await invoke<SelectionPreset>('update_selection_preset', {
+    folderPath,
+    name,
+    files,
+    patterns,
+  });
+}
+
+export async function renameSelectionPreset(
+  folderPath: string,
+  name: string,
+  newName: string
+): Promise<SelectionPreset> {
+  return await invoke<SelectionPreset>('rename_selection_preset', { folderPath, name, newName });
+}
+
+export async function deleteSelectionPreset(folderPath: string, name: string): Promise<void> {
+  await invoke('delete_selection_preset', { folderPath, name });
+}
+
+export async function loadSelectionPreset(folderPath: string, name: string): Promise<string[]> {
+  return await invoke<string[]>('load_selection_preset', { folderPath, name });
+}
+
+export async function previewSelectionPatterns(
+  folderPath: string,
+  patterns: SelectionPatterns
+): Promise<string[]> {
+  return await invoke<string[]>('preview_selection_patterns', { folderPath, patterns });
+}
+
+export async function getRecentFolders(limit?: number): Promise<RecentFolder[]> {
+  return await invoke<RecentFolder[]>('get_recent_folders', { limit });
+}
+
+export async function exportSettingsBundle(path: string): Promise<void> {
+  await invoke('export_settings_bundle', { path });
+}
+
+export async function importSettingsBundle(path: string, mode: ImportMode): Promise<ImportReport> {
+  return await invoke<ImportReport>('import_settings_bundle', { path, mode });
+}
+
+/**
+ * Watch a scanned folder, calling `onChanges` as files are added, removed or
+ * renamed. Returns the paths pruned from saved selections on start and a
+ * function that stops watching.
+ */
+export async function watchDirectory(
+  dirPath: string,
+  onChanges: (batch: FsChangeBatch) => void
+): Promise<{ pruned: string[]; stop: () => Promise<void> }> {
+  const unlisten = await listen<FsChangeBatch>('fs-changes', (event) => onChanges(event.payload));
+  let pruned: string[];
+  try {
+    pruned = await invoke<string[]>('watch_directory', { dirPath });
+  } catch (e) {
+    unlisten();
+    throw e;
+  }
+  return {
+    pruned,
+    stop: async () => {
+      unlisten();
+      await invoke('unwatch_directory');
+    },
+  };
+}
@@ -1,7 +1,62 @@
+export type TruncationMode =
+  | { mode: 'reject' }
+  | { mode: 'head_tail'; head: number; tail: number };
+
+export interface FileLimits {
+  max_bytes: number | null;
+  max_lines: number | null;
+  max_tokens: number | null;
+  truncation: TruncationMode;
+}
+
 export interface SettingsData {
   dark_mode: boolean;
   recent_sources: string[];
   last_folder: string;
+  file_limits?: FileLimits;
+  api_server?: ApiServerSettings;
+  max_scan_depth?: number;
+  scan_symlinks?: SymlinkPolicy;
+  show_hidden?: boolean;
+  hidden_allowlist?: string[];
+}
+
+export type SymlinkPolicy = 'skip' | 'follow_within_root' | 'follow_all';
+
+export interface ApiServerSettings {
+  enabled: boolean;
+  port: number;
+  token: string;
+}
+
+export type InclusionMode = 'full' | 'outline';
+
+export interface GenerateOptions {
+  limits?: FileLimits;
+  line_numbers?: boolean;
+  minify?: boolean;
+  mode?: InclusionMode;
+  file_modes?: Record<string, InclusionMode>;
+  git_diff?: string;
+  git_log?: number;
+}
+
+export interface FileReport {
+  path: string;
+  bytes_saved: number;
+  outlined: boolean;
+  error?: string;
+}
+
+export type OutputTarget =
+  | { kind: 'file' }
+  | { kind: 'clipboard'; max_bytes?: number | null };
+
+export interface GenerationReport {
+  message: string;
+  files: FileReport[];
+  copied_to_clipboard: boolean;
+  output_path: string | null;
 }
 
 export interface FileNode {
@@ -9,5 +64,103 @@ export interface FileNode {
   path: string;
   is_dir: boolean;
   children?: FileNode[];
+  /** Set by `listDirectory`, which leaves `children` empty */
+  has_children?: boolean;
+  /** Directory with entries beyond the depth limit that were not scanned */
+  truncated?: boolean;
+  /** Resolved target when the entry is a symbolic link */
+  symlink_target?: string;
+}
+
+export interface DirListing {
+  parent: string;
+  nodes: FileNode[];
+}
+
+export interface ScanBatch {
+  scan_id: number;
+  listings: DirListing[];
+  done: boolean;
+  error: string | null;
+}
+
+
+export type GitChangeSet =
+  | { kind: 'working_tree' }
+  | { kind: 'staged' }
+  | { kind: 'against_ref'; reference: string };
+
+export interface ChangedFile {
+  path: string;
+  status: string;
+}
+
+export interface SelectionPatterns {
+  include: string[];
+  exclude: string[];
+}
+
+export interface SelectionPreset {
+  name: string;
+  files: string[];
+  patterns?: SelectionPatterns;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface RecentFolder {
+  path: string;
+  last_used: number;
 }
 
+export interface ProjectConfig {
+  source_name: string | null;
+  scan: {
+    ignore: string[];
+    max_depth: number | null;
+    symlinks: SymlinkPolicy | null;
+    include_hidden: boolean | null;
+    hidden_allowlist: string[];
+  };
+  selection: SelectionPatterns | null;
+  output: {
+    format: 'text' | null;
+    line_numbers: boolean | null;
+    minify: boolean | null;
+    mode: InclusionMode | null;
+  };
+  limits: {
+    max_bytes: number | null;
+    max_lines: number | null;
+    max_tokens: number | null;
+    truncation: TruncationMode | null;
+  };
+}
+
+export type ImportMode = 'merge' | 'replace';
+
+export interface ImportConflict {
+  folder: string | null;
+  name: string;
+  resolution: string;
+}
+
+export interface ImportReport {
+  settings_replaced: boolean;
+  folders_added: number;
+  folders_merged: number;
+  presets_added: number;
+  conflicts: ImportConflict[];
+  missing_folders: string[];
+}
+
+export type FsChange =
+  | { kind: 'added'; path: string; is_dir: boolean }
+  | { kind: 'removed'; path: string }
+  | { kind: 'renamed'; from: string; to: string };
+
+export interface FsChangeBatch {
+  root: string;
+  changes: FsChange[];
+  pruned: string[];
+}